@@ -0,0 +1,236 @@
+use crate::domain::Domain;
+use crate::profile::Profile;
+use crate::semantics::Semantics;
+
+// One operation of the virtual machine. Most of them are a single instruction
+// of the language, jumps hold the index of the op they jump to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Push(i64),              // a
+    Pop,                    // b
+    Subtract,               // c
+    Decrement,              // d
+    Modulo,                 // e
+    PrintChar,              // f
+    Add,                    // g
+    ReadNumber,             // h
+    Increment,              // i
+    ReadChar,               // j
+    SkipIfZero(usize),      // k
+    Swap,                   // l
+    Multiply,               // m
+    Equals,                 // n
+    RemoveNth,              // o
+    Divide,                 // p
+    Duplicate,              // q
+    Depth,                  // r
+    SwapNth,                // s
+    JumpIfZero(usize),      // t
+    JumpIfNotZero(usize),   // u
+    IncrementBy5,           // v
+    DecrementBy5,           // w
+    PrintNumber,            // x
+    Clear,                  // y
+    Halt,                   // z
+    // `a` and `y` of the cell profile, where the only cell is overwritten/emptied.
+    ResetCell,
+    EmptyCell,
+    // A run of `i`/`v` or of `d`/`w` merged into one addition. It is applied only if the
+    // top item is between `min` and `max`, so that every step stays in the domain.
+    // Otherwise the run is executed instruction by instruction, so that range errors
+    // and the overflow policy are the same as without merging.
+    AddConst { delta: i64, min: i64, max: i64 },
+    // Instructions that leave the stack as it was, like `qb`. They still fail on a
+    // stack with less than `depth` items or without room for `room` more items, in
+    // that case they are executed one by one to report the same error.
+    Guard { depth: usize, room: usize },
+    // A `tdu` loop counting the top item down to 0. Executed one by one if the
+    // top item is missing or negative.
+    ZeroTop,
+}
+
+impl Op {
+    // The op of a single instruction, `None` if the profile ignores it.
+    // Jump targets are left to 0 and filled by `compile`.
+    pub fn from_instruction(instruction: char, profile: Profile) -> Option<Op> {
+        if !profile.contains(instruction) {
+            return None;
+        }
+        let op = match instruction {
+            'a' if profile == Profile::Cell => Op::ResetCell,
+            'y' if profile == Profile::Cell => Op::EmptyCell,
+            'a' => Op::Push(0),
+            'b' => Op::Pop,
+            'c' => Op::Subtract,
+            'd' => Op::Decrement,
+            'e' => Op::Modulo,
+            'f' => Op::PrintChar,
+            'g' => Op::Add,
+            'h' => Op::ReadNumber,
+            'i' => Op::Increment,
+            'j' => Op::ReadChar,
+            'k' => Op::SkipIfZero(0),
+            'l' => Op::Swap,
+            'm' => Op::Multiply,
+            'n' => Op::Equals,
+            'o' => Op::RemoveNth,
+            'p' => Op::Divide,
+            'q' => Op::Duplicate,
+            'r' => Op::Depth,
+            's' => Op::SwapNth,
+            't' => Op::JumpIfZero(0),
+            'u' => Op::JumpIfNotZero(0),
+            'v' => Op::IncrementBy5,
+            'w' => Op::DecrementBy5,
+            'x' => Op::PrintNumber,
+            'y' => Op::Clear,
+            'z' => Op::Halt,
+            _ => return None,
+        };
+        Some(op)
+    }
+}
+
+// The instructions of the program an op was compiled from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub index: usize,
+    pub len: usize,
+}
+
+// Change of the top item made by `i`, `v`, `d` and `w`, and the range the top item must
+// be in for the result to stay in the domain. Shared by the interpreter and the compiler.
+pub fn adjustment(instruction: char, domain: &Domain, semantics: Semantics) -> Option<(i64, i64, i64)> {
+    let step = match instruction {
+        'i' => 1,
+        'v' => 5,
+        'd' => -1,
+        'w' => -5,
+        _ => return None,
+    };
+    let domain = semantics.result_domain(instruction, domain);
+    Some((step, domain.min.saturating_sub(step), domain.max.saturating_sub(step)))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Bytecode {
+    pub ops: Vec<Op>,
+    pub spans: Vec<Span>,
+    // For each instruction of the program (plus the end of it), the first op
+    // compiled from that instruction or from one after it.
+    op_at: Vec<usize>,
+}
+
+impl Bytecode {
+    // Bytecode whose jumps are already resolved, `len` is the length of the program.
+    pub fn new(ops: Vec<Op>, spans: Vec<Span>, len: usize) -> Bytecode {
+        let mut op_at = Vec::with_capacity(len + 1);
+        let mut op_idx = 0;
+        for idx in 0..len {
+            while op_idx < spans.len() && spans[op_idx].index + spans[op_idx].len <= idx {
+                op_idx += 1;
+            }
+            op_at.push(op_idx);
+        }
+        op_at.push(ops.len());
+        Bytecode { ops, spans, op_at }
+    }
+
+    // The op starting exactly at the given instruction. `None` if that instruction is in
+    // the middle of a merged op. The end of the program maps to `ops.len()`.
+    pub fn op_starting_at(&self, index: usize) -> Option<usize> {
+        let op_idx = *self.op_at.get(index.min(self.op_at.len() - 1))?;
+        match self.spans.get(op_idx) {
+            Some(span) if span.index < index => None,
+            _ => Some(op_idx),
+        }
+    }
+}
+
+// Merges a run of `i`/`v` or `d`/`w` starting at `start`, stopping before `boundaries`.
+// Returns the merged op and the number of instructions it covers, if there are at least two.
+fn merge_adjustments(prog_str: &[char], start: usize, boundaries: &[bool], profile: Profile, domain: &Domain, semantics: Semantics) -> Option<(Op, usize)> {
+    let increasing = match adjustment(prog_str[start], domain, semantics) {
+        Some((step, _, _)) if profile.contains(prog_str[start]) => step > 0,
+        _ => return None,
+    };
+    let mut delta: i64 = 0;
+    let mut min = i64::MIN;
+    let mut max = i64::MAX;
+    let mut len = 0;
+    while start + len < prog_str.len() {
+        let ch = prog_str[start + len];
+        if len > 0 && boundaries[start + len] {
+            break;
+        }
+        let (step, step_min, step_max) = match adjustment(ch, domain, semantics) {
+            Some(x) if profile.contains(ch) && (x.0 > 0) == increasing => x,
+            _ => break,
+        };
+        // The top item is `top + delta` when this instruction runs
+        min = min.max(step_min.saturating_sub(delta));
+        max = max.min(step_max.saturating_sub(delta));
+        delta += step;
+        len += 1;
+    }
+    if len < 2 {
+        return None;
+    }
+    Some((Op::AddConst { delta, min, max }, len))
+}
+
+// Lowers the program to bytecode. `jumps` is the jump table of the program:
+// the index of the matching `u` of each `t` and of the matching `t` of each `u`.
+pub fn compile(prog_str: &[char], jumps: &[usize], profile: Profile, domain: &Domain, semantics: Semantics) -> Bytecode {
+    let len = prog_str.len();
+    // Instructions that are the target of a jump must start an op
+    let mut boundaries = vec![false; len + 1];
+    for (idx, ch) in prog_str.iter().enumerate() {
+        if !profile.contains(*ch) {
+            continue;
+        }
+        match ch {
+            'k' => {
+                boundaries[(idx + 1).min(len)] = true;
+                boundaries[(idx + 2).min(len)] = true;
+            },
+            't' | 'u' => boundaries[(jumps[idx] + 1).min(len)] = true,
+            _ => {}
+        }
+    }
+
+    let mut ops = Vec::new();
+    let mut spans = Vec::new();
+    let mut idx = 0;
+    while idx < len {
+        match merge_adjustments(prog_str, idx, &boundaries, profile, domain, semantics) {
+            Some((merged, merged_len)) => {
+                ops.push(merged);
+                spans.push(Span { index: idx, len: merged_len });
+                idx += merged_len;
+            },
+            None => {
+                // Ignored instructions get no op, they map to the next one
+                if let Some(op) = Op::from_instruction(prog_str[idx], profile) {
+                    ops.push(op);
+                    spans.push(Span { index: idx, len: 1 });
+                }
+                idx += 1;
+            },
+        }
+    }
+
+    // Resolve jumps, now that every instruction has its op
+    let mut bytecode = Bytecode::new(ops, spans, len);
+    let op_at = &bytecode.op_at;
+    let target = |instruction_idx: usize| op_at[instruction_idx.min(len)];
+    for (op, span) in bytecode.ops.iter_mut().zip(bytecode.spans.iter()) {
+        *op = match *op {
+            Op::SkipIfZero(_) => Op::SkipIfZero(target(span.index + 2)),
+            Op::JumpIfZero(_) => Op::JumpIfZero(target(jumps[span.index] + 1)),
+            Op::JumpIfNotZero(_) => Op::JumpIfNotZero(target(jumps[span.index] + 1)),
+            op => op,
+        };
+    }
+    bytecode
+}
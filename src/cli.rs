@@ -0,0 +1,301 @@
+use std::result::Result;
+use std::time::Duration;
+use ssl::Config;
+use ssl::Domain;
+use ssl::Encoding;
+use ssl::Eof;
+use ssl::NumberInput;
+use ssl::Profile;
+use ssl::Semantics;
+
+pub const USAGE: &str = "\
+Usage: ssl <command> [options] [<program>|<path>]
+
+Commands:
+    run <program>     run the program given on the command line
+    file <path>       run the program stored in <path>
+    debug <path>      run the program stored in <path> under the debugger
+    check <path>      parse the program stored in <path> and report errors
+    fmt <path>        print the program stored in <path> without whitespace and comments
+    repl              read programs from stdin line by line and run them on the same stack
+    help              print this message
+
+Use `-` as <program> or <path> to read the program from stdin.
+
+Options:
+    -e, --expr        treat the argument of debug/check/fmt as a program instead of a path
+    -d, --debug       run/file: run the program under the debugger
+    --break-on-error  debugger: prompt at an instruction that fails instead of ending the program
+    --dump <path>     run/file: write the stack and program index to <path> if the program fails
+    -p, --profile <name>
+                      instruction set to use: cell, stack-basic, arith or full (default)
+    -s, --stack-size <n>
+                      how many items the stack can hold (default 100), or `unbounded`
+    --min-value <n>   smallest value a stack item can hold (default 0)
+    --max-value <n>   largest value a stack item can hold (default 1000)
+    --overflow <policy>
+                      what happens to a value outside of the range: error (default),
+                      wrap or saturate
+    --eof <behavior>  what h and j do at the end of the input: error (default), zero,
+                      unchanged (push nothing) or a value to push as a sentinel
+    --numbers <mode>  how h reads numbers: line (one per line, default) or stream
+                      (separated by any whitespace)
+    --encoding <name> how f prints and j reads characters: byte (raw bytes), latin1 (default)
+                      or utf8 (whole code points)
+    --semantics <name>
+                      legacy (default): c pushes the 2nd item minus the top item, and v/w
+                      can't reach the edges of the value range, as the original interpreter
+                      (which didn't check the results of c, g and m against the range);
+                      spec: c pushes the top item minus the 2nd item, as the specification
+    --lenient-loops   accept unmatched t/u: a lone t jumps to the end of the program,
+                      a lone u to its start
+    --no-optimize     run the bytecode as compiled, without the peephole optimizer
+    --unbuffered      print every character as soon as f runs, for interactive programs
+                      (by default the output is flushed before h/j and when the program stops)
+    --fuel <n>        run/file/repl: stop after executing <n> instructions (per line in the repl)
+    --timeout <seconds>
+                      run/file: stop the program after <seconds>, even while it waits for input
+    --max-output <bytes>
+                      run/file: stop the program once it printed more than <bytes>
+    -h, --help        print this message
+
+Exit codes:
+    0    the program ended
+    1    the program failed
+    2    invalid arguments, or the program could not be read, parsed or loaded
+    3    the program ran out of fuel
+    4    the program timed out
+    5    the program reached the output limit
+";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Run,
+    File,
+    Debug,
+    Check,
+    Fmt,
+    Repl,
+    Help,
+}
+
+// Where the program text comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Stdin,
+    Expression(String),
+    Path(String),
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub command: Command,
+    pub source: Option<Source>,
+    pub debug: bool,
+    pub break_on_error: bool,
+    pub dump: Option<String>,
+    pub profile: Profile,
+    pub domain: Domain,
+    pub stack_size: Option<usize>,
+    pub eof: Eof,
+    pub number_input: NumberInput,
+    pub encoding: Encoding,
+    pub semantics: Semantics,
+    pub lenient_loops: bool,
+    pub optimize: bool,
+    pub unbuffered: bool,
+    pub fuel: Option<u64>,
+    pub timeout: Option<Duration>,
+    pub max_output: Option<u64>,
+}
+
+fn parse_command(name: &str) -> Result<Command, String> {
+    match name {
+        "run" => Ok(Command::Run),
+        "file" => Ok(Command::File),
+        "debug" => Ok(Command::Debug),
+        "check" => Ok(Command::Check),
+        "fmt" => Ok(Command::Fmt),
+        "repl" => Ok(Command::Repl),
+        "help" => Ok(Command::Help),
+        _ => Err(format!("unknown command `{}`", name)),
+    }
+}
+
+// Value of an option given either as `--name value` or as `--name=value`.
+fn option_value<'a, I>(name: &str, inline_value: Option<&'a str>, args: &mut I) -> Result<&'a str, String>
+where
+    I: Iterator<Item = &'a String>,
+{
+    match inline_value {
+        Some(value) => Ok(value),
+        None => match args.next() {
+            Some(value) => Ok(value),
+            None => Err(format!("missing value for `{}`", name)),
+        },
+    }
+}
+
+// Parses the arguments following the executable name.
+// Flags can appear anywhere, `--` stops flag parsing.
+pub fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut positional: Vec<&str> = Vec::new();
+    let mut expr = false;
+    let mut debug = false;
+    let mut help = false;
+    let mut profile = Profile::default();
+    let mut domain = Domain::default();
+    let mut stack_size = Config::default().max_stack_size;
+    let mut eof = Eof::default();
+    let mut number_input = NumberInput::default();
+    let mut encoding = Encoding::default();
+    let mut semantics = Semantics::default();
+    let mut break_on_error = false;
+    let mut dump = None;
+    let mut lenient_loops = false;
+    let mut optimize = true;
+    let mut unbuffered = false;
+    let mut fuel = None;
+    let mut timeout = None;
+    let mut max_output = None;
+    let mut only_positional = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if only_positional || arg == "-" || !arg.starts_with('-') {
+            positional.push(arg);
+            continue;
+        }
+        let (name, inline_value) = match arg.find('=') {
+            Some(pos) if arg.starts_with("--") => (&arg[..pos], Some(&arg[pos + 1..])),
+            _ => (arg.as_str(), None),
+        };
+        let takes_value = matches!(
+            name,
+            "-p" | "--profile" | "-s" | "--stack-size" | "--min-value" | "--max-value" | "--overflow" | "--eof" | "--numbers" | "--encoding" | "--semantics" | "--fuel" | "--timeout" | "--max-output" | "--dump"
+        );
+        if inline_value.is_some() && !takes_value {
+            return Err(format!("option `{}` does not take a value", name));
+        }
+        match name {
+            "--" => only_positional = true,
+            "-e" | "--expr" => expr = true,
+            "-d" | "--debug" => debug = true,
+            "-h" | "--help" => help = true,
+            "--break-on-error" => break_on_error = true,
+            "--lenient-loops" => lenient_loops = true,
+            "--no-optimize" => optimize = false,
+            "--unbuffered" => unbuffered = true,
+            "-p" | "--profile" => {
+                profile = option_value(name, inline_value, &mut args)?.parse()?;
+            },
+            "-s" | "--stack-size" => {
+                let value = option_value(name, inline_value, &mut args)?;
+                stack_size = match value {
+                    "unbounded" => None,
+                    _ => match value.parse::<usize>() {
+                        Ok(x) => Some(x),
+                        Err(_) => return Err(format!("invalid value `{}` for `{}`, expected a number of items or `unbounded`", value, name)),
+                    },
+                };
+            },
+            "--min-value" | "--max-value" => {
+                let value = option_value(name, inline_value, &mut args)?;
+                let value = match value.parse::<i64>() {
+                    Ok(x) => x,
+                    Err(_) => return Err(format!("invalid value `{}` for `{}`, expected an integer", value, name)),
+                };
+                if name == "--min-value" {
+                    domain.min = value;
+                } else {
+                    domain.max = value;
+                }
+            },
+            "--overflow" => {
+                domain.overflow = option_value(name, inline_value, &mut args)?.parse()?;
+            },
+            "--eof" => {
+                eof = option_value(name, inline_value, &mut args)?.parse()?;
+            },
+            "--numbers" => {
+                number_input = option_value(name, inline_value, &mut args)?.parse()?;
+            },
+            "--encoding" => {
+                encoding = option_value(name, inline_value, &mut args)?.parse()?;
+            },
+            "--semantics" => {
+                semantics = option_value(name, inline_value, &mut args)?.parse()?;
+            },
+            "--fuel" => {
+                let value = option_value(name, inline_value, &mut args)?;
+                match value.parse::<u64>() {
+                    Ok(x) => fuel = Some(x),
+                    Err(_) => return Err(format!("invalid value `{}` for `{}`, expected a number of instructions", value, name)),
+                }
+            },
+            "--timeout" => {
+                let value = option_value(name, inline_value, &mut args)?;
+                match value.parse::<f64>().ok().filter(|x| *x > 0.0).and_then(|x| Duration::try_from_secs_f64(x).ok()) {
+                    Some(x) => timeout = Some(x),
+                    None => return Err(format!("invalid value `{}` for `{}`, expected a positive number of seconds", value, name)),
+                }
+            },
+            "--dump" => {
+                dump = Some(option_value(name, inline_value, &mut args)?.to_string());
+            },
+            "--max-output" => {
+                let value = option_value(name, inline_value, &mut args)?;
+                match value.parse::<u64>() {
+                    Ok(x) => max_output = Some(x),
+                    Err(_) => return Err(format!("invalid value `{}` for `{}`, expected a number of bytes", value, name)),
+                }
+            },
+            _ => return Err(format!("unknown option `{}`", arg)),
+        }
+    }
+
+    if help {
+        return Ok(Options { command: Command::Help, source: None, debug, break_on_error, dump, profile, domain, stack_size, eof, number_input, encoding, semantics, lenient_loops, optimize, unbuffered, fuel, timeout, max_output });
+    }
+    let command = match positional.first() {
+        Some(name) => parse_command(name)?,
+        None => return Err(String::from("missing command")),
+    };
+    let takes_source = !matches!(command, Command::Repl | Command::Help);
+    let expected_args = if takes_source { 2 } else { 1 };
+    if positional.len() > expected_args {
+        return Err(format!("unexpected argument `{}`", positional[expected_args]));
+    }
+    let source = if takes_source {
+        let arg = match positional.get(1) {
+            Some(x) => x.to_string(),
+            None => return Err(String::from("missing program or path")),
+        };
+        Some(if arg == "-" {
+            Source::Stdin
+        } else if command == Command::Run || expr {
+            Source::Expression(arg)
+        } else {
+            Source::Path(arg)
+        })
+    } else {
+        None
+    };
+    if debug && !matches!(command, Command::Run | Command::File) {
+        return Err(String::from("--debug can only be used with run and file"));
+    }
+    if fuel.is_some() && (debug || command == Command::Debug) {
+        return Err(String::from("--fuel cannot be used with the debugger"));
+    }
+    let debugging = debug || command == Command::Debug;
+    if break_on_error && !debugging {
+        return Err(String::from("--break-on-error can only be used with the debugger"));
+    }
+    if dump.is_some() && (debugging || !matches!(command, Command::Run | Command::File)) {
+        return Err(String::from("--dump can only be used with run and file, without the debugger"));
+    }
+    let limited = timeout.is_some() || max_output.is_some();
+    if limited && (debug || !matches!(command, Command::Run | Command::File)) {
+        return Err(String::from("--timeout and --max-output can only be used with run and file, without the debugger"));
+    }
+    Ok(Options { command, source, debug, break_on_error, dump, profile, domain, stack_size, eof, number_input, encoding, semantics, lenient_loops, optimize, unbuffered, fuel, timeout, max_output })
+}
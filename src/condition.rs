@@ -0,0 +1,142 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::stack::Stack;
+
+// A value of the machine state that the debugger can test or watch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    // The top item of the stack.
+    Top,
+    // The number of items on the stack.
+    Depth,
+    // The item at this position, counting from the bottom of the stack (as `pstack` prints it).
+    Slot(usize),
+    // A constant.
+    Value(i64),
+}
+
+impl Operand {
+    // The current value, `None` if the stack has no such item.
+    pub fn value(self, memory: &Stack<i64>) -> Option<i64> {
+        match self {
+            Operand::Top => memory.last().copied(),
+            Operand::Depth => Some(memory.length() as i64),
+            Operand::Slot(n) if n < memory.length() => memory.peek(memory.length() - 1 - n).copied(),
+            Operand::Slot(_) => None,
+            Operand::Value(x) => Some(x),
+        }
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operand::Top => write!(f, "top"),
+            Operand::Depth => write!(f, "depth"),
+            Operand::Slot(n) => write!(f, "stack[{}]", n),
+            Operand::Value(x) => write!(f, "{}", x),
+        }
+    }
+}
+
+impl FromStr for Operand {
+    type Err = String;
+
+    // `top`, `depth`, `stack[<n>]` or an integer.
+    fn from_str(text: &str) -> Result<Operand, String> {
+        match text {
+            "top" => return Ok(Operand::Top),
+            "depth" => return Ok(Operand::Depth),
+            _ => {},
+        }
+        if let Some(slot) = text.strip_prefix("stack[").and_then(|rest| rest.strip_suffix(']')) {
+            return match slot.trim().parse::<usize>() {
+                Ok(n) => Ok(Operand::Slot(n)),
+                Err(_) => Err(format!("invalid stack slot `{}`", slot)),
+            };
+        }
+        match text.parse::<i64>() {
+            Ok(x) => Ok(Operand::Value(x)),
+            Err(_) => Err(format!("unknown operand `{}`, expected top, depth, stack[<n>] or an integer", text)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    // Two-character operators first, so that `<=` isn't read as `<`.
+    pub const ALL: [Comparison; 6] = [
+        Comparison::Equal, Comparison::NotEqual, Comparison::LessOrEqual,
+        Comparison::GreaterOrEqual, Comparison::Less, Comparison::Greater,
+    ];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+        }
+    }
+
+    pub fn holds(self, left: i64, right: i64) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+// A comparison of two operands, such as `top == 0` or `depth > 50`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    pub left: Operand,
+    pub comparison: Comparison,
+    pub right: Operand,
+}
+
+impl Condition {
+    // False when an operand refers to an item the stack doesn't have.
+    pub fn holds(&self, memory: &Stack<i64>) -> bool {
+        match (self.left.value(memory), self.right.value(memory)) {
+            (Some(left), Some(right)) => self.comparison.holds(left, right),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.left, self.comparison.symbol(), self.right)
+    }
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Condition, String> {
+        for comparison in Comparison::ALL.iter() {
+            if let Some(pos) = text.find(comparison.symbol()) {
+                let left = text[..pos].trim().parse()?;
+                let right = text[pos + comparison.symbol().len()..].trim().parse()?;
+                return Ok(Condition { left, comparison: *comparison, right });
+            }
+        }
+        Err(format!("invalid condition `{}`, expected <operand> <comparison> <operand>", text))
+    }
+}
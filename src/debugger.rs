@@ -0,0 +1,577 @@
+use std::fmt;
+use std::io;
+use std::result::Result;
+use crate::condition::Condition;
+use crate::condition::Operand;
+use crate::error::InterpreterError;
+use crate::history::Change;
+use crate::error::Position;
+use crate::interpreter::Interpreter;
+use crate::io::Io;
+use crate::io::StdIo;
+use crate::stack::Stack;
+
+// Prints a line to the console of the debugger, as `println!` does to stdout.
+macro_rules! say {
+    ($console:expr, $($arg:tt)*) => {{
+        let _ = $console.write_str(&format!("{}\n", format_args!($($arg)*)));
+    }};
+}
+
+const HELP: &str = "\
+Commands are:
+pidx                print current program instruction index
+pprog               print current program text
+pstack              print current stack
+pist                print current program instruction
+step                execute the next instruction
+cont                continue until next breakpoint/end of program
+brk  <index>        insert a breakpoint at the given index
+brk  <index> if <condition>
+                    stop at the index only when the condition holds
+brk  if <condition> stop before any instruction where the condition holds
+tbreak <index>      insert a breakpoint deleted once it is hit
+watch <value>       stop once an instruction changed top or depth, or wrote stack[<n>]
+info breaks         list the breakpoints and how many times they were hit
+delete <number>     delete a breakpoint
+disable <number>    keep a breakpoint but don't stop at it
+enable <number>     stop at a disabled breakpoint again
+clear               delete every breakpoint
+exec <character>    execute the instruction <character>
+rstep               undo the last instruction
+rcont               undo instructions until the previous breakpoint/watchpoint
+                    (the input is read again, what was printed stays printed)
+quit                end the program
+Conditions compare top, depth, stack[<n>] (from the bottom) or integers
+with == != < <= > >=, for example `top == 0` or `stack[2] == 65`.";
+
+// What makes a breakpoint stop the program.
+#[derive(Debug, Clone, PartialEq)]
+enum Trigger {
+    // Before the instruction at `index`, or before any instruction if `None`,
+    // when `condition` holds.
+    Location { index: Option<usize>, condition: Option<Condition> },
+    // After an instruction changed `operand`, or wrote it for a stack slot, `last` is the value it had.
+    Watch { operand: Operand, last: Option<i64> },
+}
+
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Trigger::Location { index, condition } => {
+                match index {
+                    Some(index) => write!(f, "at {}", index)?,
+                    None => write!(f, "anywhere")?,
+                }
+                match condition {
+                    Some(condition) => write!(f, " if {}", condition),
+                    None => Ok(()),
+                }
+            },
+            Trigger::Watch { operand, .. } => write!(f, "on {}", operand),
+        }
+    }
+}
+
+// A breakpoint set with `brk`, `tbreak` or `watch`, identified by its number in the commands.
+#[derive(Debug, Clone)]
+struct Breakpoint {
+    number: usize,
+    trigger: Trigger,
+    enabled: bool,
+    // Deleted the first time it is hit.
+    temporary: bool,
+    hits: usize,
+}
+
+impl Breakpoint {
+    fn kind(&self) -> &'static str {
+        match self.trigger {
+            Trigger::Watch { .. } => "watchpoint",
+            Trigger::Location { .. } if self.temporary => "tbreak",
+            Trigger::Location { .. } => "breakpoint",
+        }
+    }
+
+    // True if the breakpoint is enabled and stops before the instruction at `index`, with this stack.
+    fn stops_at(&self, index: usize, memory: &Stack<i64>) -> bool {
+        match &self.trigger {
+            Trigger::Location { index: at, condition } if self.enabled => {
+                at.is_none_or(|at| at == index) && condition.is_none_or(|condition| condition.holds(memory))
+            },
+            _ => false,
+        }
+    }
+
+    // How messages refer to the breakpoint.
+    fn label(&self) -> &'static str {
+        match self.trigger {
+            Trigger::Watch { .. } => "Watchpoint",
+            Trigger::Location { .. } if self.temporary => "Temporary breakpoint",
+            Trigger::Location { .. } => "Breakpoint",
+        }
+    }
+}
+
+// The location of `brk <index> [if <condition>]` or `brk if <condition>`.
+fn parse_location(args: &str) -> Result<Trigger, String> {
+    let (index, condition) = match args.strip_prefix("if ") {
+        Some(condition) => ("", Some(condition)),
+        None => match args.split_once(" if ") {
+            Some((index, condition)) => (index, Some(condition)),
+            None => (args, None),
+        },
+    };
+    let index = match index.trim() {
+        "" => None,
+        index => match index.parse::<usize>() {
+            Ok(x) => Some(x),
+            Err(_) => return Err(String::from("Error: breakpoint should be a positive number")),
+        },
+    };
+    let condition = match condition.map(|text| text.parse::<Condition>()) {
+        Some(Ok(x)) => Some(x),
+        Some(Err(err)) => return Err(format!("Error: {}", err)),
+        None => None,
+    };
+    if index.is_none() && condition.is_none() {
+        return Err(String::from("Invalid brk command"));
+    }
+    Ok(Trigger::Location { index, condition })
+}
+
+// The number given to a breakpoint command, such as `delete <number>`.
+fn breakpoint_number(cmd: &str) -> Result<usize, String> {
+    match cmd.split_ascii_whitespace().nth(1).map(|arg| arg.parse::<usize>()) {
+        Some(Ok(x)) => Ok(x),
+        Some(Err(_)) => Err(String::from("Error: breakpoint number should be a positive number")),
+        None => Err(String::from("Error: missing breakpoint number")),
+    }
+}
+
+// Interactive debugger: stops before every instruction until `cont` is used,
+// then again whenever an enabled breakpoint is reached or a watched value is written.
+// The commands are read from the console `C` and the messages printed to it, the
+// program itself goes through the `Io` of its interpreter.
+pub struct Debugger<I: Io = StdIo, C: Io = StdIo> {
+    interpreter: Interpreter<I>,
+    console: C,
+    breakpoint_enabled: bool,
+    breakpoints: Vec<Breakpoint>,
+    // Number of the next breakpoint, numbers are never reused
+    next_breakpoint: usize,
+    // Prompt at a failing instruction instead of ending the program.
+    break_on_error: bool,
+    // The error the program stopped on, until the instruction is retried successfully.
+    failure: Option<InterpreterError>,
+}
+
+impl<I: Io> Debugger<I> {
+    // Prompts on the terminal. Its output isn't buffered, so that the messages come
+    // before the next prompt.
+    pub fn new(interpreter: Interpreter<I>) -> Debugger<I> {
+        Debugger::with_console(interpreter, StdIo::unbuffered())
+    }
+}
+
+impl<I: Io, C: Io> Debugger<I, C> {
+    // The interpreter records the instructions it executes, for `rstep` and `rcont`.
+    pub fn with_console(mut interpreter: Interpreter<I>, console: C) -> Debugger<I, C> {
+        interpreter.set_recording(true);
+        Debugger {
+            interpreter,
+            console,
+            breakpoint_enabled: true,
+            breakpoints: Vec::new(),
+            next_breakpoint: 1,
+            break_on_error: false,
+            failure: None,
+        }
+    }
+
+    // When enabled, a failing instruction doesn't end the program: the debugger prompts
+    // with the state left by the failure, `step` and `cont` retry the instruction.
+    pub fn set_break_on_error(&mut self, enabled: bool) {
+        self.break_on_error = enabled;
+    }
+
+    pub fn interpreter(&self) -> &Interpreter<I> {
+        &self.interpreter
+    }
+
+    pub fn console(&self) -> &C {
+        &self.console
+    }
+
+    // Prints the prompt and reads a command, without its line ending.
+    fn prompt_user(&mut self) -> io::Result<String> {
+        self.console.write_str("\ndebug> ")?;
+        self.console.flush()?;
+        let mut input = String::new();
+        if self.console.read_line(&mut input)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "end of input"));
+        }
+        Ok(input.trim().to_string())
+    }
+
+    // Runs the program under the debugger, the output is flushed before returning.
+    pub fn run(&mut self) -> Result<(), InterpreterError> {
+        let result = self.debug();
+        let _ = self.interpreter.io_mut().flush();
+        result
+    }
+
+    // Adds a breakpoint, unless there already is the same one.
+    fn set_breakpoint(&mut self, trigger: Trigger, temporary: bool) {
+        if let Trigger::Location { index: Some(index), .. } = trigger {
+            if index >= self.interpreter.program().len() {
+                say!(self.console, "Error: breakpoint out of range");
+                return;
+            }
+        }
+        if let Some(existing) = self.breakpoints.iter().find(|brk| brk.trigger == trigger) {
+            say!(self.console, "Error: breakpoint {} is already set {}", existing.number, trigger);
+            return;
+        }
+        let number = self.next_breakpoint;
+        self.next_breakpoint += 1;
+        let brk = Breakpoint { number, trigger, enabled: true, temporary, hits: 0 };
+        say!(self.console, "{} {} set {}", brk.label(), number, brk.trigger);
+        self.breakpoints.push(brk);
+    }
+
+    // Watches `top`, `depth` or `stack[<n>]`.
+    fn set_watchpoint(&mut self, operand: &str) {
+        let operand = match operand.parse::<Operand>() {
+            Ok(Operand::Value(_)) => {
+                say!(self.console, "Error: a constant never changes, watch top, depth or stack[<n>]");
+                return;
+            },
+            Ok(x) => x,
+            Err(err) => {
+                say!(self.console, "Error: {}", err);
+                return;
+            },
+        };
+        let last = operand.value(self.interpreter.memory());
+        self.set_breakpoint(Trigger::Watch { operand, last }, false);
+    }
+
+    fn breakpoint_mut(&mut self, cmd: &str) -> Result<&mut Breakpoint, String> {
+        let number = breakpoint_number(cmd)?;
+        match self.breakpoints.iter_mut().find(|brk| brk.number == number) {
+            Some(x) => Ok(x),
+            None => Err(format!("Error: no breakpoint number {}", number)),
+        }
+    }
+
+    fn print_breakpoints(&mut self) {
+        if self.breakpoints.is_empty() {
+            say!(self.console, "No breakpoints");
+            return;
+        }
+        say!(self.console, "Num   Type        Enabled  Hits   Where");
+        for brk in &self.breakpoints {
+            let enabled = if brk.enabled { "yes" } else { "no" };
+            say!(self.console, "{:<5} {:<11} {:<8} {:<6} {}", brk.number, brk.kind(), enabled, brk.hits, brk.trigger);
+        }
+    }
+
+    // Counts a hit on the enabled breakpoints that stop before the instruction at `index`,
+    // and deletes the temporary ones. Returns true if the program must stop.
+    fn hit_breakpoint(&mut self, index: usize) -> bool {
+        let memory = self.interpreter.memory();
+        let mut stop = false;
+        for brk in self.breakpoints.iter_mut() {
+            if !brk.stops_at(index, memory) {
+                continue;
+            }
+            brk.hits += 1;
+            stop = true;
+            if brk.temporary {
+                say!(self.console, "Temporary breakpoint {} hit at {}, deleted", brk.number, index);
+            } else {
+                say!(self.console, "Breakpoint {} hit at {} ({} time(s))", brk.number, index, brk.hits);
+            }
+        }
+        self.breakpoints.retain(|brk| !(brk.temporary && brk.hits > 0));
+        stop
+    }
+
+    // Stops going back at the enabled breakpoints before the instruction at `index`.
+    // Unlike `hit_breakpoint`, nothing is counted and temporary breakpoints are kept:
+    // the program didn't reach them, it went back to them.
+    fn reached_breakpoint(&mut self, index: usize) -> bool {
+        let memory = self.interpreter.memory();
+        let mut stop = false;
+        for brk in self.breakpoints.iter().filter(|brk| brk.stops_at(index, memory)) {
+            say!(self.console, "Back at {} {} (index {})", brk.label().to_lowercase(), brk.number, index);
+            stop = true;
+        }
+        stop
+    }
+
+    // The change of the last recorded instruction and the depth it left the stack with,
+    // to tell which slots it wrote.
+    fn last_write(&self) -> Option<(Change, usize)> {
+        let change = self.interpreter.last_change()?.clone();
+        Some((change, self.interpreter.memory().length()))
+    }
+
+    // Compares the watched values to the ones they had before the last instruction.
+    // `stack[<n>]` also stops when `written`, the change of that instruction, wrote the
+    // slot with the value it had. Returns true if an enabled watchpoint saw a change.
+    // Going back (`forward` false), the changes are reported but not counted as hits.
+    fn check_watchpoints(&mut self, forward: bool, written: Option<(Change, usize)>) -> bool {
+        let memory = self.interpreter.memory();
+        let mut stop = false;
+        for brk in self.breakpoints.iter_mut() {
+            let (operand, last) = match &mut brk.trigger {
+                Trigger::Watch { operand, last } => (*operand, last),
+                Trigger::Location { .. } => continue,
+            };
+            let value = operand.value(memory);
+            let rewritten = match (operand, &written) {
+                (Operand::Slot(n), Some((change, depth))) => value == *last && change.writes(n, *depth),
+                _ => false,
+            };
+            if value == *last && !rewritten {
+                continue;
+            }
+            // Disabled watchpoints keep track of the value, so that enabling them doesn't stop right away
+            let old = std::mem::replace(last, value);
+            if brk.enabled {
+                if forward {
+                    brk.hits += 1;
+                }
+                stop = true;
+                let show = |value: Option<i64>| value.map_or(String::from("none"), |x| x.to_string());
+                if rewritten {
+                    say!(self.console, "Watchpoint {}: {} written, still {}", brk.number, operand, show(value));
+                } else {
+                    say!(self.console, "Watchpoint {}: {} changed from {} to {}", brk.number, operand, show(old), show(value));
+                }
+            }
+        }
+        stop
+    }
+
+    // Undoes the last instruction. Returns false if there was nothing to undo.
+    fn reverse_step(&mut self) -> bool {
+        let written = self.last_write();
+        if !self.interpreter.undo() {
+            return false;
+        }
+        // Only reports the changes, the program stops anyway
+        self.check_watchpoints(false, written);
+        true
+    }
+
+    // Undoes instructions until the state is the one of an enabled breakpoint or a
+    // watched slot is written. Returns false if there was nothing to undo.
+    fn reverse_continue(&mut self) -> bool {
+        let mut moved = false;
+        loop {
+            let written = self.last_write();
+            if !self.interpreter.undo() {
+                if moved {
+                    say!(self.console, "Reached the oldest recorded instruction");
+                }
+                return moved;
+            }
+            moved = true;
+            if self.check_watchpoints(false, written) || self.reached_breakpoint(self.interpreter.program_index()) {
+                return true;
+            }
+        }
+    }
+
+    fn debug(&mut self) -> Result<(), InterpreterError> {
+        // False after an `exec` that stayed on the same instruction
+        let mut arrived = true;
+        while let Some(mut curr_instruction) = self.interpreter.current_instruction() {
+            let curr_prog_idx = self.interpreter.program_index();
+            let mut executing_cmd = false;
+            let mut asking_user = true;
+            // Set once `rstep` or `rcont` moved back to an earlier instruction
+            let mut rewound = false;
+            // What the program printed so far must come before the debugger messages
+            let _ = self.interpreter.io_mut().flush();
+            if arrived && self.hit_breakpoint(curr_prog_idx) {
+                self.breakpoint_enabled = true;
+            }
+            while asking_user && self.breakpoint_enabled {
+                // Show what the program printed so far before prompting
+                let _ = self.interpreter.io_mut().flush();
+                let cmd = match self.prompt_user() {
+                    Ok(x) => x,
+                    Err(x) => {
+                        if let Some(err) = self.failure.take() {
+                            return Err(err);
+                        }
+                        let at = Position {
+                            instruction: curr_instruction,
+                            index: curr_prog_idx,
+                            depth: self.interpreter.memory().length(),
+                        };
+                        let reason = format!("cannot read debugger command: {}", x);
+                        return Err(InterpreterError::InputError { at, reason });
+                    }
+                };
+                match cmd.as_str() {
+                    "help" => {
+                        // print help : help
+                        say!(self.console, "{}", HELP);
+                    },
+                    "pidx" => {
+                        // print current prog index : pidx
+                        say!(self.console, "Program index: {}", curr_prog_idx);
+                    },
+                    "pprog" => {
+                        // print the whole prog : pprog
+                        say!(self.console, "Program: {}", self.interpreter.program().iter().collect::<String>());
+                    },
+                    "pstack" => {
+                        // print current stack : pstack
+                        say!(self.console, "Stack: {:?}", self.interpreter.memory());
+                    },
+                    "pist" => {
+                        // print current prog instruction : pist
+                        say!(self.console, "Stack: {}", curr_instruction);
+                    },
+                    "step" => {
+                        // execute next instruction: step
+                        asking_user = false;
+                    },
+                    "cont" => {
+                        // run until completion/breakpoint : cont
+                        asking_user = false;
+                        self.breakpoint_enabled = false;
+                    },
+                    "rstep" | "rcont" => {
+                        // go back one instruction : rstep
+                        // go back to the previous breakpoint : rcont
+                        let moved = if cmd == "rstep" {
+                            self.reverse_step()
+                        } else {
+                            self.reverse_continue()
+                        };
+                        if moved {
+                            self.failure = None;
+                            rewound = true;
+                            asking_user = false;
+                        } else {
+                            say!(self.console, "Error: no earlier instruction recorded");
+                        }
+                    },
+                    "quit" => {
+                        // end the program, with its error if it failed : quit
+                        return match self.failure.take() {
+                            Some(err) => Err(err),
+                            None => Ok(()),
+                        };
+                    },
+                    brk_cmd if brk_cmd.starts_with("brk ") || brk_cmd.starts_with("tbreak ") => {
+                        // breakpoint at given index (from 0): brk <index> [if <condition>]
+                        // before any instruction where the condition holds: brk if <condition>
+                        // deleted after its first hit: tbreak <index> [if <condition>]
+                        let (name, args) = brk_cmd.split_once(' ').unwrap_or((brk_cmd, ""));
+                        match parse_location(args.trim()) {
+                            Ok(trigger) => self.set_breakpoint(trigger, name == "tbreak"),
+                            Err(err) => say!(self.console, "{}", err),
+                        }
+                    },
+                    watch_cmd if watch_cmd.starts_with("watch ") => {
+                        // stop when a value changes or a slot is written : watch <top|depth|stack[n]>
+                        self.set_watchpoint(watch_cmd["watch ".len()..].trim());
+                    },
+                    "info breaks" => {
+                        // list the breakpoints : info breaks
+                        self.print_breakpoints();
+                    },
+                    delete_cmd if delete_cmd.starts_with("delete ") => {
+                        // delete a breakpoint : delete <number>
+                        match breakpoint_number(delete_cmd) {
+                            Ok(number) => match self.breakpoints.iter().position(|brk| brk.number == number) {
+                                Some(pos) => {
+                                    let brk = self.breakpoints.remove(pos);
+                                    say!(self.console, "{} {} deleted", brk.label(), number);
+                                },
+                                None => say!(self.console, "Error: no breakpoint number {}", number),
+                            },
+                            Err(err) => say!(self.console, "{}", err),
+                        }
+                    },
+                    toggle_cmd if toggle_cmd.starts_with("disable ") || toggle_cmd.starts_with("enable ") => {
+                        // stop or not at a breakpoint : disable <number>, enable <number>
+                        let enabled = toggle_cmd.starts_with("enable ");
+                        match self.breakpoint_mut(toggle_cmd) {
+                            Ok(brk) => {
+                                brk.enabled = enabled;
+                                let message = format!("{} {} {}", brk.label(), brk.number, if enabled { "enabled" } else { "disabled" });
+                                say!(self.console, "{}", message);
+                            },
+                            Err(err) => say!(self.console, "{}", err),
+                        }
+                    },
+                    "clear" => {
+                        // delete every breakpoint : clear
+                        say!(self.console, "Deleted {} breakpoint(s)", self.breakpoints.len());
+                        self.breakpoints.clear();
+                    },
+                    exec_cmd if exec_cmd.starts_with("exec ") => {
+                        // execute the given operator: exec <character>
+                        let exec_chr = match exec_cmd.split_ascii_whitespace()
+                            .nth(1)
+                            .and_then(|arg| arg.chars().next()) {
+                            Some(x) => x,
+                            None => {
+                                say!(self.console, "Error: invalid exec command");
+                                continue;
+                            }
+                        };
+                        curr_instruction = exec_chr;
+                        executing_cmd = true;
+                        asking_user = false;
+                    },
+                    _ => {
+                        say!(self.console, "Invalid command");
+                    },
+                };
+            }
+            if rewound {
+                // Prompt again at the instruction it went back to
+                arrived = false;
+                continue;
+            }
+            let result = if executing_cmd {
+                self.interpreter.execute(curr_instruction)
+            } else {
+                self.interpreter.step()
+            };
+            match result {
+                // An `exec` doesn't retry the failed instruction, the program still has its error
+                Ok(()) if !executing_cmd => self.failure = None,
+                Ok(()) => {},
+                Err(err) if self.break_on_error => {
+                    // The program index is still the one of the failed instruction, prompt there
+                    let _ = self.interpreter.io_mut().flush();
+                    say!(self.console, "Error: {}", err);
+                    say!(self.console, "The program stopped on this error: `step` and `cont` retry the instruction, `quit` ends the program");
+                    self.failure = Some(err);
+                    self.breakpoint_enabled = true;
+                    arrived = false;
+                    continue;
+                },
+                Err(err) => return Err(err),
+            }
+            arrived = !executing_cmd || self.interpreter.program_index() != curr_prog_idx;
+            let _ = self.interpreter.io_mut().flush();
+            let written = self.last_write();
+            if self.check_watchpoints(true, written) {
+                self.breakpoint_enabled = true;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use std::fmt;
+use crate::source_map::Location;
+use crate::source_map::SourceMap;
+
+// An error message together with the source line it refers to, rendered like:
+//
+// error: `b` at index 0: stack underflow, needs 1 item(s) but the stack has 0
+//  --> hello.ssl:1:1
+//   |
+// 1 | b // pop
+//   | ^
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub file: String,
+    pub location: Option<Location>,
+    pub source_line: Option<String>,
+}
+
+impl Diagnostic {
+    // A diagnostic pointing at the given location of the source.
+    pub fn at_location(message: &str, source_map: &SourceMap, location: Location) -> Diagnostic {
+        Diagnostic {
+            message: message.to_string(),
+            file: source_map.name().to_string(),
+            location: Some(location),
+            source_line: source_map.line(location.line).map(|x| x.to_string()),
+        }
+    }
+
+    // A diagnostic pointing at the source of the instruction at `index` in the program.
+    pub fn at_index(message: &str, source_map: &SourceMap, index: usize) -> Diagnostic {
+        match source_map.location(index) {
+            Some(location) => Diagnostic::at_location(message, source_map, location),
+            None => Diagnostic {
+                message: message.to_string(),
+                file: source_map.name().to_string(),
+                location: None,
+                source_line: None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error: {}", self.message)?;
+        let location = match self.location {
+            Some(x) => x,
+            None => return write!(f, "\n --> {}", self.file),
+        };
+        let gutter = " ".repeat(location.line.to_string().len());
+        write!(f, "\n{}--> {}:{}:{}", gutter, self.file, location.line, location.column)?;
+        if let Some(source_line) = &self.source_line {
+            // Keep the tabs of the source line so that the caret stays aligned
+            let padding: String = source_line.chars()
+                .take(location.column - 1)
+                .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+                .collect();
+            write!(f, "\n{} |", gutter)?;
+            write!(f, "\n{} | {}", location.line, source_line)?;
+            write!(f, "\n{} | {}^", gutter, padding)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+use std::str::FromStr;
+
+// What happens to a value that falls outside of the domain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    // The instruction fails with a `ValueOutOfRange` error.
+    #[default]
+    Error,
+    // The value wraps around: `max + 1` is `min` and `min - 1` is `max`.
+    Wrap,
+    // The value is clamped to `min` or `max`.
+    Saturate,
+}
+
+impl Overflow {
+    pub const ALL: [Overflow; 3] = [Overflow::Error, Overflow::Wrap, Overflow::Saturate];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Overflow::Error => "error",
+            Overflow::Wrap => "wrap",
+            Overflow::Saturate => "saturate",
+        }
+    }
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Overflow {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Overflow, String> {
+        match Overflow::ALL.iter().find(|overflow| overflow.name() == name) {
+            Some(overflow) => Ok(*overflow),
+            None => Err(format!("unknown overflow policy `{}`", name)),
+        }
+    }
+}
+
+// The values a stack item can hold, from `min` to `max` included. Every instruction
+// producing a value (`a c e g h i j m n p r v w d`) goes through `apply`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Domain {
+    pub min: i64,
+    pub max: i64,
+    pub overflow: Overflow,
+}
+
+impl Default for Domain {
+    fn default() -> Domain {
+        Domain {
+            min: 0,
+            max: 1000,
+            overflow: Overflow::Error,
+        }
+    }
+}
+
+impl Domain {
+    pub fn is_empty(&self) -> bool {
+        self.min > self.max
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        value >= self.min && value <= self.max
+    }
+
+    // The value to store for the result of an instruction, `None` if it is out of
+    // the domain and the policy is to fail.
+    pub fn apply(&self, value: i128) -> Option<i64> {
+        let min = self.min as i128;
+        let max = self.max as i128;
+        if value >= min && value <= max {
+            return Some(value as i64);
+        }
+        match self.overflow {
+            Overflow::Error => None,
+            Overflow::Wrap => Some((min + (value - min).rem_euclid(max - min + 1)) as i64),
+            Overflow::Saturate => Some(value.clamp(min, max) as i64),
+        }
+    }
+}
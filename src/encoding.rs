@@ -0,0 +1,83 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+// How `f` turns a value into output and how `j` turns input into a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    // One raw byte per value, from 0 to 255.
+    Byte,
+    // Values from 0 to 255 are the Latin-1 characters, printed as UTF-8.
+    // `j` reads one byte.
+    #[default]
+    Latin1,
+    // Values are Unicode code points, printed as UTF-8. `j` reads a whole code point.
+    Utf8,
+}
+
+impl Encoding {
+    pub const ALL: [Encoding; 3] = [Encoding::Byte, Encoding::Latin1, Encoding::Utf8];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Byte => "byte",
+            Encoding::Latin1 => "latin1",
+            Encoding::Utf8 => "utf8",
+        }
+    }
+
+    // The bytes `f` prints for `value`, `None` if it is not a character of the encoding.
+    pub fn encode(self, value: i64) -> Option<Vec<u8>> {
+        match self {
+            Encoding::Byte => u8::try_from(value).ok().map(|byte| vec![byte]),
+            Encoding::Latin1 => u8::try_from(value).ok().map(|byte| (byte as char).to_string().into_bytes()),
+            Encoding::Utf8 => {
+                let ch = u32::try_from(value).ok().and_then(char::from_u32)?;
+                Some(ch.to_string().into_bytes())
+            },
+        }
+    }
+
+    // Number of bytes of the character starting with `first`, `None` if no character
+    // of the encoding starts with it.
+    pub fn sequence_length(self, first: u8) -> Option<usize> {
+        match self {
+            Encoding::Byte | Encoding::Latin1 => Some(1),
+            Encoding::Utf8 => match first {
+                0x00..=0x7f => Some(1),
+                0xc2..=0xdf => Some(2),
+                0xe0..=0xef => Some(3),
+                0xf0..=0xf4 => Some(4),
+                _ => None,
+            },
+        }
+    }
+
+    // The value of a complete character read by `j`, `None` if the bytes are not valid.
+    pub fn decode(self, bytes: &[u8]) -> Option<i64> {
+        match self {
+            Encoding::Byte | Encoding::Latin1 => bytes.first().map(|byte| *byte as i64),
+            Encoding::Utf8 => {
+                let ch = std::str::from_utf8(bytes).ok()?.chars().next()?;
+                Some(ch as i64)
+            },
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Encoding, String> {
+        match Encoding::ALL.iter().find(|encoding| encoding.name() == name) {
+            Some(encoding) => Ok(*encoding),
+            None => Err(format!("unknown encoding `{}`", name)),
+        }
+    }
+}
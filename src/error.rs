@@ -0,0 +1,148 @@
+use std::error::Error;
+use std::fmt;
+use crate::diagnostic::Diagnostic;
+use crate::encoding::Encoding;
+use crate::source_map::SourceMap;
+
+// Where an instruction failed: the instruction itself, its index in the program
+// and the depth of the stack right before it was executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub instruction: char,
+    pub index: usize,
+    pub depth: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}` at index {}", self.instruction, self.index)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InterpreterError {
+    // The instruction needs more items than the stack holds.
+    StackUnderflow { at: Position, needed: usize },
+    // The stack is full and the instruction tried to push an item.
+    StackOverflow { at: Position, max_size: usize },
+    // The instruction would produce a value outside of the domain, from `min` to `max`.
+    ValueOutOfRange { at: Position, value: i128, min: i64, max: i64 },
+    // `p` or `e` with 0 as the 2nd item.
+    DivisionByZero { at: Position },
+    // Reading from the input failed or the input is not valid for the instruction.
+    InputError { at: Position, reason: String },
+    // Printing the output of the program failed.
+    OutputError { at: Position, reason: String },
+    // `f` can't print the value as a character of the encoding.
+    NotACharacter { at: Position, value: i64, encoding: Encoding },
+    // `o` or `s` referenced an item that is not on the stack.
+    IndexOutOfRange { at: Position, index: i64 },
+}
+
+impl InterpreterError {
+    pub fn position(&self) -> &Position {
+        match self {
+            InterpreterError::StackUnderflow { at, .. } => at,
+            InterpreterError::StackOverflow { at, .. } => at,
+            InterpreterError::ValueOutOfRange { at, .. } => at,
+            InterpreterError::DivisionByZero { at } => at,
+            InterpreterError::InputError { at, .. } => at,
+            InterpreterError::OutputError { at, .. } => at,
+            InterpreterError::NotACharacter { at, .. } => at,
+            InterpreterError::IndexOutOfRange { at, .. } => at,
+        }
+    }
+}
+
+impl fmt::Display for InterpreterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InterpreterError::StackUnderflow { at, needed } => {
+                write!(f, "{}: stack underflow, needs {} item(s) but the stack has {}", at, needed, at.depth)
+            },
+            InterpreterError::StackOverflow { at, max_size } => {
+                write!(f, "{}: stack overflow, the stack is limited to {} items", at, max_size)
+            },
+            InterpreterError::ValueOutOfRange { at, value, min, max } => {
+                write!(f, "{}: value {} is out of range, values should stay between {} and {}", at, value, min, max)
+            },
+            InterpreterError::DivisionByZero { at } => {
+                write!(f, "{}: dividing by zero", at)
+            },
+            InterpreterError::InputError { at, reason } => {
+                write!(f, "{}: {}", at, reason)
+            },
+            InterpreterError::OutputError { at, reason } => {
+                write!(f, "{}: {}", at, reason)
+            },
+            InterpreterError::NotACharacter { at, value, encoding } => {
+                write!(f, "{}: value {} is not a character in the {} encoding", at, value, encoding)
+            },
+            InterpreterError::IndexOutOfRange { at, index } => {
+                write!(f, "{}: stack index {} is out of range, the stack has {} item(s)", at, index, at.depth)
+            },
+        }
+    }
+}
+
+impl Error for InterpreterError {}
+
+// A `t` that no `u` closes, or a `u` that closes no `t`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnmatchedLoop {
+    pub instruction: char,
+    pub index: usize,
+}
+
+impl fmt::Display for UnmatchedLoop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.instruction {
+            't' => write!(f, "unmatched `t` at index {}: no `u` closes this loop", self.index),
+            _ => write!(f, "unmatched `u` at index {}: no `t` opens this loop", self.index),
+        }
+    }
+}
+
+// The program can't be loaded into an interpreter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    // Every unmatched `t` and `u`, in program order.
+    UnmatchedLoops(Vec<UnmatchedLoop>),
+    // The configured value domain holds no value.
+    EmptyDomain { min: i64, max: i64 },
+    // The value `--eof` pushes at the end of the input is outside of the value domain.
+    SentinelOutOfRange { value: i64, min: i64, max: i64 },
+}
+
+impl LoadError {
+    // One diagnostic per problem, pointing at the source of the program.
+    pub fn diagnostics(&self, source_map: &SourceMap) -> Vec<Diagnostic> {
+        match self {
+            LoadError::UnmatchedLoops(loops) => loops.iter()
+                .map(|unmatched| Diagnostic::at_index(&unmatched.to_string(), source_map, unmatched.index))
+                .collect(),
+            LoadError::EmptyDomain { .. } | LoadError::SentinelOutOfRange { .. } => {
+                vec![Diagnostic::at_index(&self.to_string(), source_map, usize::MAX)]
+            },
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::UnmatchedLoops(loops) => {
+                let descriptions: Vec<String> = loops.iter().map(|x| x.to_string()).collect();
+                write!(f, "{}", descriptions.join(", "))
+            },
+            LoadError::EmptyDomain { min, max } => {
+                write!(f, "the value domain is empty, the minimum {} is above the maximum {}", min, max)
+            },
+            LoadError::SentinelOutOfRange { value, min, max } => {
+                write!(f, "the end of input value {} is out of range, values should stay between {} and {}", value, min, max)
+            },
+        }
+    }
+}
+
+impl Error for LoadError {}
@@ -0,0 +1,110 @@
+use std::collections::VecDeque;
+use crate::stack::Stack;
+
+// How many instructions can be undone, the oldest ones are forgotten.
+pub const HISTORY_LIMIT: usize = 100_000;
+
+// How an instruction changed the stack, only what is needed to revert it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    // Left the stack as it was (`f`, `k`, `t`, `u`, `x`, `z`, a `h` that read nothing...).
+    None,
+    // Pushed one item.
+    Push,
+    // Popped `value`.
+    Pop(i64),
+    // Overwrote the item at `index`, counting from the bottom, which was `value`.
+    Set { index: usize, value: i64 },
+    // Swapped the items at these indices, counting from the bottom.
+    Swap(usize, usize),
+    // Removed `value` from `index`, counting from the bottom.
+    Remove { index: usize, value: i64 },
+    // Replaced the whole stack, which held these items (`y`, and `a` of the cell profile).
+    Replace(Vec<i64>),
+}
+
+impl Change {
+    // Whether the change wrote the item at `slot`, counting from the bottom, even with the
+    // value it already had. `depth` is the number of items it left on the stack. Removing
+    // an item writes every slot above it, and the slots it vacated count as written.
+    pub fn writes(&self, slot: usize, depth: usize) -> bool {
+        match self {
+            Change::None => false,
+            Change::Push => slot + 1 == depth,
+            Change::Pop(_) => slot == depth,
+            Change::Set { index, .. } => slot == *index,
+            Change::Swap(a, b) => slot == *a || slot == *b,
+            Change::Remove { index, .. } => slot >= *index && slot <= depth,
+            Change::Replace(items) => slot < items.len().max(depth),
+        }
+    }
+
+    // Puts the stack back in the state it had before the change.
+    pub fn revert(self, memory: &mut Stack<i64>) {
+        // The change was made to this very stack, its indices are valid and the items
+        // put back fit, so the stack errors can't happen
+        match self {
+            Change::None => {},
+            Change::Push => {
+                memory.pop();
+            },
+            Change::Pop(value) => {
+                let _ = memory.insert(memory.length(), value);
+            },
+            Change::Set { index, value } => {
+                let _ = memory.set(index, value);
+            },
+            Change::Swap(a, b) => {
+                let _ = memory.swap(a, b);
+            },
+            Change::Remove { index, value } => {
+                let _ = memory.insert(index, value);
+            },
+            Change::Replace(items) => memory.replace(items),
+        }
+    }
+}
+
+// What an executed instruction changed, enough to restore the state before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Undo {
+    pub program_index: usize,
+    pub halted: bool,
+    pub change: Change,
+    // The input the instruction read, given back when it is undone.
+    pub input: Vec<u8>,
+}
+
+// The undo log of the instructions executed by `run`, `step` and `execute`, latest last.
+#[derive(Debug, Default)]
+pub struct History {
+    entries: VecDeque<Undo>,
+    // Input read so far by the instruction being recorded
+    pub consumed: Vec<u8>,
+}
+
+impl History {
+    pub fn push(&mut self, undo: Undo) {
+        if self.entries.len() == HISTORY_LIMIT {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(undo);
+    }
+
+    pub fn pop(&mut self) -> Option<Undo> {
+        self.entries.pop_back()
+    }
+
+    // The latest entry, the one `pop` returns.
+    pub fn last(&self) -> Option<&Undo> {
+        self.entries.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,82 @@
+use std::fmt;
+use std::str::FromStr;
+
+// What `h` and `j` do once the input is exhausted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Eof {
+    // The instruction fails with an `InputError`.
+    #[default]
+    Error,
+    // Pushes 0.
+    Zero,
+    // Pushes the given value. A program is only loaded if the value is in the value domain.
+    Sentinel(i64),
+    // Pushes nothing.
+    Unchanged,
+}
+
+impl fmt::Display for Eof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Eof::Error => write!(f, "error"),
+            Eof::Zero => write!(f, "zero"),
+            Eof::Sentinel(value) => write!(f, "{}", value),
+            Eof::Unchanged => write!(f, "unchanged"),
+        }
+    }
+}
+
+impl FromStr for Eof {
+    type Err = String;
+
+    // `error`, `zero`, `unchanged` or the value of the sentinel.
+    fn from_str(name: &str) -> Result<Eof, String> {
+        match name {
+            "error" => Ok(Eof::Error),
+            "zero" => Ok(Eof::Zero),
+            "unchanged" => Ok(Eof::Unchanged),
+            _ => match name.parse::<i64>() {
+                Ok(value) => Ok(Eof::Sentinel(value)),
+                Err(_) => Err(format!("unknown end of input behavior `{}`", name)),
+            },
+        }
+    }
+}
+
+// How `h` splits the input into numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberInput {
+    // One number per line.
+    #[default]
+    Line,
+    // Numbers separated by any whitespace, including several on the same line.
+    Stream,
+}
+
+impl NumberInput {
+    pub const ALL: [NumberInput; 2] = [NumberInput::Line, NumberInput::Stream];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NumberInput::Line => "line",
+            NumberInput::Stream => "stream",
+        }
+    }
+}
+
+impl fmt::Display for NumberInput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for NumberInput {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<NumberInput, String> {
+        match NumberInput::ALL.iter().find(|mode| mode.name() == name) {
+            Some(mode) => Ok(*mode),
+            None => Err(format!("unknown number input mode `{}`", name)),
+        }
+    }
+}
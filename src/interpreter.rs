@@ -0,0 +1,997 @@
+use std::collections::VecDeque;
+use std::io;
+use std::mem;
+use std::result::Result;
+use crate::bytecode;
+use crate::bytecode::Bytecode;
+use crate::bytecode::Op;
+use crate::bytecode::Span;
+use crate::domain::Domain;
+use crate::encoding::Encoding;
+use crate::error::InterpreterError;
+use crate::error::LoadError;
+use crate::error::Position;
+use crate::error::UnmatchedLoop;
+use crate::history::Change;
+use crate::history::History;
+use crate::history::Undo;
+use crate::input::Eof;
+use crate::input::NumberInput;
+use crate::io::Io;
+use crate::io::StdIo;
+use crate::optimizer;
+use crate::profile::Profile;
+use crate::semantics::Semantics;
+use crate::stack::Stack;
+use crate::stack::StackError;
+
+fn underflow(at: Position, needed: usize) -> InterpreterError {
+    InterpreterError::StackUnderflow { at, needed }
+}
+
+fn out_of_range(domain: &Domain, at: Position, value: i128) -> InterpreterError {
+    InterpreterError::ValueOutOfRange { at, value, min: domain.min, max: domain.max }
+}
+
+// Brings the result of an instruction into the domain, following its overflow policy.
+fn in_domain(domain: &Domain, at: Position, value: i128) -> Result<i64, InterpreterError> {
+    domain.apply(value).ok_or_else(|| out_of_range(domain, at, value))
+}
+
+fn stack_error(memory: &Stack<i64>, at: Position, err: StackError) -> InterpreterError {
+    match err {
+        StackError::Overflow => InterpreterError::StackOverflow { at, max_size: memory.max_size().unwrap_or(usize::MAX) },
+        StackError::IndexOutOfRange(idx) => InterpreterError::IndexOutOfRange { at, index: idx as i64 },
+    }
+}
+
+fn push(memory: &mut Stack<i64>, at: Position, value: i64) -> Result<(), InterpreterError> {
+    memory.push(value).map_err(|err| stack_error(memory, at, err))
+}
+
+fn top(memory: &Stack<i64>, at: Position) -> Result<i64, InterpreterError> {
+    memory.last().copied().ok_or_else(|| underflow(at, 1))
+}
+
+// Applies `i`, `v`, `d` or `w` to the top item.
+fn adjust(memory: &mut Stack<i64>, domain: &Domain, semantics: Semantics, at: Position) -> Result<(), InterpreterError> {
+    let (step, _, _) = bytecode::adjustment(at.instruction, domain, semantics).unwrap_or((0, 0, 0));
+    let elem = memory.last_mut().ok_or_else(|| underflow(at, 1))?;
+    *elem = in_domain(&semantics.result_domain(at.instruction, domain), at, *elem as i128 + step as i128)?;
+    Ok(())
+}
+
+// Index, from the bottom of the stack, of the item `o` and `s` refer to: the top item
+// is the position of that item counting from the top, 0 being the top item itself.
+fn indexed_item(memory: &Stack<i64>, at: Position) -> Result<usize, InterpreterError> {
+    let n = top(memory, at)?;
+    let len = memory.length();
+    if n < 0 || n as u64 >= len as u64 {
+        return Err(InterpreterError::IndexOutOfRange { at, index: n });
+    }
+    Ok(len - 1 - n as usize)
+}
+
+// The top item and the one below it.
+fn top_two(memory: &Stack<i64>, at: Position) -> Result<(i64, i64), InterpreterError> {
+    match (memory.peek(0), memory.peek(1)) {
+        (Some(top), Some(second)) => Ok((*top, *second)),
+        _ => Err(underflow(at, 2)),
+    }
+}
+
+fn input_error(at: Position, reason: String) -> InterpreterError {
+    InterpreterError::InputError { at, reason }
+}
+
+// The next line of input, `None` at the end of the input.
+fn read_line<I: Io>(io: &mut I, at: Position) -> Result<Option<String>, InterpreterError> {
+    let mut input = String::new();
+    match io.read_line(&mut input) {
+        Ok(0) => Ok(None),
+        Ok(_) => Ok(Some(input)),
+        Err(x) => Err(input_error(at, format!("readline failed: {}", x))),
+    }
+}
+
+// The next whitespace-separated word of input, `None` if only whitespace is left.
+// The whitespace right after the word is consumed with it.
+fn read_word<I: Io>(io: &mut I, at: Position) -> Result<Option<String>, InterpreterError> {
+    let mut word = Vec::new();
+    loop {
+        match io.read_byte() {
+            Ok(Some(byte)) if byte.is_ascii_whitespace() => {
+                if !word.is_empty() {
+                    break;
+                }
+            },
+            Ok(Some(byte)) => word.push(byte),
+            Ok(None) => break,
+            Err(x) => return Err(input_error(at, format!("cannot get input: {}", x))),
+        }
+    }
+    if word.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&word).into_owned()))
+}
+
+// The next character of input, `None` at the end of the input.
+fn read_char<I: Io>(io: &mut I, encoding: Encoding, at: Position) -> Result<Option<i64>, InterpreterError> {
+    let read_byte = |io: &mut I| io.read_byte().map_err(|x| input_error(at, format!("cannot get input: {}", x)));
+    let invalid = || input_error(at, format!("input is not valid {} text", encoding));
+    let first = match read_byte(io)? {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let len = encoding.sequence_length(first).ok_or_else(invalid)?;
+    let mut bytes = vec![first];
+    while bytes.len() < len {
+        match read_byte(io)? {
+            Some(x) => bytes.push(x),
+            None => return Err(invalid()),
+        }
+    }
+    encoding.decode(&bytes).map(Some).ok_or_else(invalid)
+}
+
+// What `h` and `j` do when there is nothing left to read.
+fn end_of_input(memory: &mut Stack<i64>, domain: &Domain, eof: Eof, at: Position, what: &str) -> Result<(), InterpreterError> {
+    let value = match eof {
+        Eof::Error => return Err(input_error(at, format!("cannot read a {}, the input ended", what))),
+        Eof::Zero => 0,
+        Eof::Sentinel(value) => value,
+        Eof::Unchanged => return Ok(()),
+    };
+    let value = in_domain(domain, at, value as i128)?;
+    push(memory, at, value)
+}
+
+fn find_corresponding_u(prog_str: &[char], t_pos: usize) -> usize {
+    let mut found_ts = 0;
+    for (idx, ch) in prog_str.iter().skip(t_pos + 1).enumerate() {
+        match ch {
+            't' => {
+                found_ts += 1;
+            },
+            'u' => {
+                if found_ts == 0 {
+                    return idx + t_pos + 1;
+                }
+                found_ts -= 1;
+            },
+            _ => {}
+        }
+    }
+    prog_str.len() - 1
+}
+
+fn find_corresponding_t(prog_str: &[char], u_pos: usize) -> usize {
+    let mut found_us = 0;
+    let u_pos_from_end = prog_str.len() - 1 - u_pos;
+    for (idx, ch) in prog_str.iter().rev().skip(u_pos_from_end + 1).enumerate() {
+        match ch {
+            't' => {
+                if found_us == 0 {
+                    return u_pos - 1 - idx;
+                }
+                found_us -= 1;
+            },
+            'u' => {
+                found_us += 1;
+            },
+            _ => {}
+        }
+    }
+    0
+}
+
+// Matches every `t` with its `u` once, so that loops don't rescan the program.
+// Entries of other instructions are unused. An unmatched `t` jumps to the last
+// instruction and an unmatched `u` to the first one, like the scans above do,
+// and both are returned so that the caller can reject the program.
+fn build_jump_table(prog_str: &[char]) -> (Vec<usize>, Vec<UnmatchedLoop>) {
+    let mut jumps = vec![0; prog_str.len()];
+    let mut unmatched = Vec::new();
+    let mut open_ts = Vec::new();
+    for (idx, ch) in prog_str.iter().enumerate() {
+        match ch {
+            't' => {
+                open_ts.push(idx);
+            },
+            'u' => {
+                match open_ts.pop() {
+                    Some(t_idx) => {
+                        jumps[t_idx] = idx;
+                        jumps[idx] = t_idx;
+                    },
+                    None => {
+                        unmatched.push(UnmatchedLoop { instruction: 'u', index: idx });
+                    },
+                }
+            },
+            _ => {}
+        }
+    }
+    for t_idx in open_ts {
+        jumps[t_idx] = prog_str.len() - 1;
+        unmatched.push(UnmatchedLoop { instruction: 't', index: t_idx });
+    }
+    unmatched.sort_by_key(|x| x.index);
+    (jumps, unmatched)
+}
+
+// Settings of an interpreter, fixed when it is created.
+#[derive(Debug, Clone)]
+pub struct Config {
+    // `None` for a stack that grows without limit.
+    pub max_stack_size: Option<usize>,
+    pub profile: Profile,
+    // Accept unmatched `t`/`u` and jump to the end/start of the program instead.
+    pub lenient_loops: bool,
+    // Values the stack items can hold, and what happens to the ones outside of it.
+    pub domain: Domain,
+    // What `h` and `j` do once the input is exhausted.
+    pub eof: Eof,
+    // Whether `h` reads one number per line or a stream of whitespace-separated numbers.
+    pub number_input: NumberInput,
+    // How `f` prints characters and `j` reads them.
+    pub encoding: Encoding,
+    // Whether `c` and the edges of `v` and `w` follow the original interpreter or the specification.
+    pub semantics: Semantics,
+    // Run the peephole optimizer on the bytecode.
+    pub optimize: bool,
+    // How many instructions `run` may execute, `None` for no limit.
+    pub fuel: Option<u64>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            max_stack_size: Some(100),
+            profile: Profile::Full,
+            lenient_loops: false,
+            domain: Domain::default(),
+            eof: Eof::default(),
+            number_input: NumberInput::default(),
+            encoding: Encoding::default(),
+            semantics: Semantics::default(),
+            optimize: true,
+            fuel: None,
+        }
+    }
+}
+
+// How `run` stopped, when no instruction failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    // The program ended or executed a `z`.
+    Finished,
+    // The fuel ran out before the next instruction. The machine is left as it was,
+    // `run` resumes from there once fuel is added.
+    OutOfFuel,
+}
+
+fn output_error(at: Position, err: io::Error) -> InterpreterError {
+    InterpreterError::OutputError { at, reason: format!("cannot print: {}", err) }
+}
+
+// The input of `h` and `j`: first what undoing instructions gave back, then `io`.
+// While recording, the bytes read are also kept in `consumed`.
+struct Input<'a, I: Io> {
+    io: &'a mut I,
+    unread: &'a mut VecDeque<u8>,
+    consumed: Option<&'a mut Vec<u8>>,
+}
+
+impl<'a, I: Io> Input<'a, I> {
+    fn record(&mut self, bytes: &[u8]) {
+        if let Some(consumed) = &mut self.consumed {
+            consumed.extend_from_slice(bytes);
+        }
+    }
+}
+
+impl<'a, I: Io> Io for Input<'a, I> {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        let mut line = Vec::new();
+        while let Some(byte) = self.unread.pop_front() {
+            line.push(byte);
+            if byte == b'\n' {
+                break;
+            }
+        }
+        if line.last() != Some(&b'\n') {
+            let mut rest = String::new();
+            if let Err(err) = self.io.read_line(&mut rest) {
+                // Keep the given back input for the next read
+                for byte in line.iter().rev() {
+                    self.unread.push_front(*byte);
+                }
+                return Err(err);
+            }
+            line.extend_from_slice(rest.as_bytes());
+        }
+        self.record(&line);
+        buf.push_str(&String::from_utf8_lossy(&line));
+        Ok(line.len())
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let byte = match self.unread.pop_front() {
+            Some(x) => Some(x),
+            None => self.io.read_byte()?,
+        };
+        if let Some(byte) = byte {
+            self.record(&[byte]);
+        }
+        Ok(byte)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.io.write_bytes(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+// A StupidStackLanguage machine: the program, its stack and the index of the
+// next instruction to execute. `h`, `j`, `f` and `x` go through `io`.
+#[derive(Debug)]
+pub struct Interpreter<I: Io = StdIo> {
+    config: Config,
+    io: I,
+    memory: Stack<i64>,
+    program: Vec<char>,
+    jumps: Vec<usize>,
+    bytecode: Bytecode,
+    // Instructions of the profile in each op, ignored ones are free
+    costs: Vec<u64>,
+    fuel: Option<u64>,
+    curr_prog_idx: usize,
+    halted: bool,
+    // Input given back by `undo`, read again before the one of `io`
+    unread: VecDeque<u8>,
+    // `None` unless recording
+    history: Option<History>,
+}
+
+impl Interpreter<StdIo> {
+    // An interpreter reading and printing through the standard input and output,
+    // with a buffered output.
+    pub fn new(program: &str, config: Config) -> Result<Interpreter, LoadError> {
+        Interpreter::with_io(program, config, StdIo::default())
+    }
+}
+
+impl<I: Io> Interpreter<I> {
+    pub fn with_io(program: &str, config: Config, io: I) -> Result<Interpreter<I>, LoadError> {
+        let mut interpreter = Interpreter {
+            memory: match config.max_stack_size {
+                Some(max_size) => Stack::new(max_size),
+                None => Stack::unbounded(),
+            },
+            io,
+            program: Vec::new(),
+            jumps: Vec::new(),
+            bytecode: Bytecode::default(),
+            costs: Vec::new(),
+            fuel: config.fuel,
+            curr_prog_idx: 0,
+            halted: false,
+            unread: VecDeque::new(),
+            history: None,
+            config,
+        };
+        interpreter.load_program(program)?;
+        Ok(interpreter)
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn io(&self) -> &I {
+        &self.io
+    }
+
+    pub fn io_mut(&mut self) -> &mut I {
+        &mut self.io
+    }
+
+    pub fn into_io(self) -> I {
+        self.io
+    }
+
+    // Replaces the program and restarts from its first instruction, keeping the stack.
+    // The current program is kept if the new one is rejected.
+    pub fn load_program(&mut self, program: &str) -> Result<(), LoadError> {
+        let domain = self.config.domain;
+        if domain.is_empty() {
+            return Err(LoadError::EmptyDomain { min: domain.min, max: domain.max });
+        }
+        if let Eof::Sentinel(value) = self.config.eof {
+            if !domain.contains(value) {
+                return Err(LoadError::SentinelOutOfRange { value, min: domain.min, max: domain.max });
+            }
+        }
+        let program: Vec<char> = program.chars().collect();
+        let (jumps, unmatched) = build_jump_table(&program);
+        // Loops only exist in profiles that have both `t` and `u`
+        let has_loops = self.config.profile.contains('t');
+        if has_loops && !self.config.lenient_loops && !unmatched.is_empty() {
+            return Err(LoadError::UnmatchedLoops(unmatched));
+        }
+        let semantics = self.config.semantics;
+        self.bytecode = bytecode::compile(&program, &jumps, self.config.profile, &domain, semantics);
+        if self.config.optimize {
+            self.bytecode = optimizer::optimize(&self.bytecode, program.len(), &domain, semantics);
+        }
+        let profile = self.config.profile;
+        self.costs = self.bytecode.spans.iter()
+            .map(|span| program[span.index..span.index + span.len].iter().filter(|ch| profile.contains(**ch)).count() as u64)
+            .collect();
+        self.program = program;
+        self.jumps = jumps;
+        self.curr_prog_idx = 0;
+        self.halted = false;
+        // The recorded instructions belong to the previous program
+        if self.history.is_some() {
+            self.history = Some(History::default());
+        }
+        Ok(())
+    }
+
+    pub fn memory(&self) -> &Stack<i64> {
+        &self.memory
+    }
+
+    pub fn program(&self) -> &[char] {
+        &self.program
+    }
+
+    pub fn bytecode(&self) -> &Bytecode {
+        &self.bytecode
+    }
+
+    // Instructions that `run` may still execute, `None` if there is no limit.
+    pub fn fuel(&self) -> Option<u64> {
+        self.fuel
+    }
+
+    pub fn set_fuel(&mut self, fuel: Option<u64>) {
+        self.fuel = fuel;
+    }
+
+    // Adds to the remaining fuel, typically to resume after `Outcome::OutOfFuel`.
+    pub fn add_fuel(&mut self, fuel: u64) {
+        if let Some(remaining) = self.fuel {
+            self.fuel = Some(remaining.saturating_add(fuel));
+        }
+    }
+
+    pub fn program_index(&self) -> usize {
+        self.curr_prog_idx
+    }
+
+    // The instruction that the next call to `step` will execute.
+    pub fn current_instruction(&self) -> Option<char> {
+        if self.is_finished() {
+            return None;
+        }
+        Some(self.program[self.curr_prog_idx])
+    }
+
+    // The program index, the instruction there and the stack as text, for post-mortem dumps.
+    pub fn dump(&self) -> String {
+        let instruction = match self.current_instruction() {
+            Some(x) => format!("`{}`", x),
+            None => String::from("none, the program ended"),
+        };
+        let max_size = match self.memory.max_size() {
+            Some(x) => x.to_string(),
+            None => String::from("unbounded"),
+        };
+        let items: Vec<String> = (0..self.memory.length()).rev()
+            .filter_map(|n| self.memory.peek(n))
+            .map(|x| x.to_string())
+            .collect();
+        format!(
+            "program index: {}\ninstruction: {}\nstack depth: {} (max {})\nstack (bottom first): [{}]\n",
+            self.curr_prog_idx, instruction, self.memory.length(), max_size, items.join(", "),
+        )
+    }
+
+    // True once the program index ran past the end or a `z` was executed.
+    pub fn is_finished(&self) -> bool {
+        self.halted || self.curr_prog_idx >= self.program.len()
+    }
+
+    // Runs the program until it ends, an instruction fails or the fuel runs out, using
+    // the bytecode. On failure the program index is the one of the failing instruction.
+    // Each executed instruction of the profile costs one unit of fuel. An op is paid
+    // for before it runs, so the fuel left after a failure is not meaningful.
+    // The output is flushed before returning.
+    pub fn run(&mut self) -> Result<Outcome, InterpreterError> {
+        let result = self.run_ops();
+        // A failure here has no instruction to point at, the program already stopped
+        let _ = self.io.flush();
+        result
+    }
+
+    fn run_ops(&mut self) -> Result<Outcome, InterpreterError> {
+        // Merged ops can't be undone, a recorded run goes one instruction at a time
+        if self.history.is_some() {
+            return self.run_metered_steps();
+        }
+        // `step` may have stopped in the middle of a merged op, finish it first
+        let mut pc = loop {
+            if self.is_finished() {
+                return Ok(Outcome::Finished);
+            }
+            match self.bytecode.op_starting_at(self.curr_prog_idx) {
+                Some(pc) => break pc,
+                None => {
+                    if !self.metered_step()? {
+                        return Ok(Outcome::OutOfFuel);
+                    }
+                },
+            }
+        };
+        while pc < self.bytecode.ops.len() {
+            if let Some(fuel) = self.fuel {
+                let cost = self.op_cost(pc);
+                if cost > fuel {
+                    // Spend the rest of the fuel one instruction at a time
+                    self.curr_prog_idx = self.bytecode.spans[pc].index;
+                    return self.run_metered_steps();
+                }
+                self.fuel = Some(fuel - cost);
+            }
+            pc = match self.run_op(pc) {
+                Ok(Some(next_pc)) => next_pc,
+                Ok(None) => return Ok(Outcome::Finished),
+                Err(err) => {
+                    self.curr_prog_idx = err.position().index;
+                    return Err(err);
+                },
+            };
+        }
+        self.curr_prog_idx = self.program.len();
+        Ok(Outcome::Finished)
+    }
+
+    // Number of instructions the op at `pc` executes.
+    fn op_cost(&self, pc: usize) -> u64 {
+        match self.bytecode.ops[pc] {
+            // The `t`, then a `d` and a `u` for each unit of the top item
+            Op::ZeroTop => match self.memory.last() {
+                Some(x) if *x >= 0 => 1 + 2 * *x as u64,
+                // Replayed one by one, with a wrapping or saturating domain it may never end
+                Some(_) => u64::MAX,
+                None => 1,
+            },
+            _ => self.costs[pc],
+        }
+    }
+
+    // Executes instructions one by one until the program ends or the fuel runs out.
+    fn run_metered_steps(&mut self) -> Result<Outcome, InterpreterError> {
+        while !self.is_finished() {
+            if !self.metered_step()? {
+                return Ok(Outcome::OutOfFuel);
+            }
+        }
+        Ok(Outcome::Finished)
+    }
+
+    // Executes the current instruction if there is fuel left for it.
+    // Returns false, without executing it, if there isn't.
+    fn metered_step(&mut self) -> Result<bool, InterpreterError> {
+        let profile = self.config.profile;
+        if self.current_instruction().is_some_and(|ch| profile.contains(ch)) {
+            match self.fuel {
+                Some(0) => return Ok(false),
+                Some(fuel) => self.fuel = Some(fuel - 1),
+                None => {},
+            }
+        }
+        self.step()?;
+        Ok(true)
+    }
+
+    // Executes the op at `pc`, returns the next one or `None` if the program halted.
+    fn run_op(&mut self, pc: usize) -> Result<Option<usize>, InterpreterError> {
+        let op = self.bytecode.ops[pc];
+        let span = self.bytecode.spans[pc];
+        let at = Position {
+            instruction: self.program[span.index],
+            index: span.index,
+            depth: self.memory.length(),
+        };
+        match op {
+            Op::SkipIfZero(target) | Op::JumpIfZero(target) => {
+                if top(&self.memory, at)? == 0 {
+                    return Ok(Some(target));
+                }
+            },
+            Op::JumpIfNotZero(target) => {
+                if top(&self.memory, at)? != 0 {
+                    return Ok(Some(target));
+                }
+            },
+            Op::Halt => {
+                self.halted = true;
+                self.curr_prog_idx = span.index + 1;
+                return Ok(None);
+            },
+            Op::AddConst { delta, min, max } => {
+                match self.memory.last_mut() {
+                    Some(x) if *x >= min && *x <= max => *x += delta,
+                    _ => self.replay(span)?,
+                }
+            },
+            Op::Guard { depth, room } => {
+                let len = self.memory.length();
+                if len < depth || !self.memory.has_room(room) {
+                    self.replay(span)?;
+                }
+            },
+            Op::ZeroTop => {
+                match self.memory.last_mut() {
+                    Some(x) if *x >= 0 => *x = 0,
+                    _ => self.replay(span)?,
+                }
+            },
+            op => self.apply(op, at)?,
+        };
+        Ok(Some(pc + 1))
+    }
+
+    // Executes the instructions of a merged op one by one.
+    fn replay(&mut self, span: Span) -> Result<(), InterpreterError> {
+        self.curr_prog_idx = span.index;
+        while self.curr_prog_idx < span.index + span.len {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    // Executes the current instruction and moves to the next one.
+    pub fn step(&mut self) -> Result<(), InterpreterError> {
+        if let Some(instruction) = self.current_instruction() {
+            self.recorded(instruction, |interpreter| {
+                interpreter.execute_instruction(instruction)?;
+                interpreter.curr_prog_idx += 1;
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+
+    // Executes `instruction` as if it was found at the current program index,
+    // without moving to the next one. Jumps (`k`, `t`, `u`) still update the index.
+    pub fn execute(&mut self, instruction: char) -> Result<(), InterpreterError> {
+        self.recorded(instruction, |interpreter| interpreter.execute_instruction(instruction))
+    }
+
+    // Starts or stops recording the instructions executed by `run`, `step` and `execute`,
+    // so that `undo` can revert them. While recording, `run` executes the program one
+    // instruction at a time instead of using the bytecode. Stopping forgets the history.
+    pub fn set_recording(&mut self, enabled: bool) {
+        self.history = if enabled { Some(History::default()) } else { None };
+    }
+
+    // Number of recorded instructions that `undo` can revert.
+    pub fn history_len(&self) -> usize {
+        self.history.as_ref().map_or(0, |history| history.len())
+    }
+
+    // How the last recorded instruction changed the stack, `None` if there is none.
+    pub fn last_change(&self) -> Option<&Change> {
+        self.history.as_ref().and_then(|history| history.last()).map(|undo| &undo.change)
+    }
+
+    // Restores the state from before the last recorded instruction: the stack, the
+    // program index and the input it read, which will be read again. What it printed
+    // stays printed. Returns false if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let undo = match self.history.as_mut().and_then(|history| history.pop()) {
+            Some(x) => x,
+            None => return false,
+        };
+        undo.change.revert(&mut self.memory);
+        self.curr_prog_idx = undo.program_index;
+        self.halted = undo.halted;
+        for byte in undo.input.iter().rev() {
+            self.unread.push_front(*byte);
+        }
+        true
+    }
+
+    // How executing `instruction` will change the stack, as far as it is known before it
+    // runs: `None` for instructions that may only push an item, since `h` and `j` may not.
+    // Also `None` if the instruction will fail.
+    fn planned_change(&self, instruction: char) -> Option<Change> {
+        let op = Op::from_instruction(instruction, self.config.profile)?;
+        let memory = &self.memory;
+        let len = memory.length();
+        let top = memory.last().copied();
+        let at = Position { instruction, index: self.curr_prog_idx, depth: len };
+        match op {
+            Op::Pop | Op::EmptyCell => top.map(Change::Pop),
+            Op::Increment | Op::IncrementBy5 | Op::Decrement | Op::DecrementBy5 => {
+                top.map(|value| Change::Set { index: len - 1, value })
+            },
+            Op::Swap if len >= 2 => Some(Change::Swap(len - 1, len - 2)),
+            Op::SwapNth => indexed_item(memory, at).ok().map(|idx| Change::Swap(len - 1, idx)),
+            Op::RemoveNth => {
+                let index = indexed_item(memory, at).ok()?;
+                Some(Change::Remove { index, value: memory.items()[index] })
+            },
+            Op::Clear | Op::ResetCell => Some(Change::Replace(memory.items().to_vec())),
+            _ => None,
+        }
+    }
+
+    // Runs `execute`, which executes `instruction`, and while recording logs how to undo
+    // it if it succeeded.
+    fn recorded<F>(&mut self, instruction: char, execute: F) -> Result<(), InterpreterError>
+        where F: FnOnce(&mut Interpreter<I>) -> Result<(), InterpreterError> {
+        if self.history.is_none() {
+            return execute(self);
+        }
+        let program_index = self.curr_prog_idx;
+        let halted = self.halted;
+        let depth = self.memory.length();
+        let change = self.planned_change(instruction);
+        let result = execute(self);
+        let history = match self.history.as_mut() {
+            Some(x) => x,
+            None => return result,
+        };
+        let input = mem::take(&mut history.consumed);
+        if result.is_ok() {
+            let change = match change {
+                Some(x) => x,
+                None if self.memory.length() > depth => Change::Push,
+                None => Change::None,
+            };
+            history.push(Undo { program_index, halted, change, input });
+        }
+        result
+    }
+
+    fn execute_instruction(&mut self, instruction: char) -> Result<(), InterpreterError> {
+        let op = match Op::from_instruction(instruction, self.config.profile) {
+            Some(x) => x,
+            None => {
+                // Instructions outside of the profile are ignored
+                return Ok(());
+            }
+        };
+        let at = Position {
+            instruction,
+            index: self.curr_prog_idx,
+            depth: self.memory.length(),
+        };
+        match instruction {
+            'k' => {
+                // Skips the next command if the top item on the stack is 0.
+                let elem = top(&self.memory, at)?;
+                if elem == 0 {
+                    self.curr_prog_idx += 1;
+                }
+            },
+            't' => {
+                // If the top item on the stack is 0, jumps to the corresponding ‘u’ in the program, otherwise does nothing.
+                let elem = top(&self.memory, at)?;
+                if elem == 0 {
+                    self.curr_prog_idx = self.jump_target('t');
+                }
+            },
+            'u' => {
+                // If the top item on the stack is not 0, jumps back to the corresponding ‘t’ in the program, otherwise does nothing.
+                let elem = top(&self.memory, at)?;
+                if elem != 0 {
+                    self.curr_prog_idx = self.jump_target('u');
+                }
+            },
+            'z' => {
+                // Exit the program.
+                self.halted = true;
+            },
+            _ => {
+                self.apply(op, at)?;
+            },
+        };
+        Ok(())
+    }
+
+    // Executes an op that doesn't change the program index.
+    fn apply(&mut self, op: Op, at: Position) -> Result<(), InterpreterError> {
+        let memory = &mut self.memory;
+        let domain = &self.config.domain;
+        match op {
+            Op::Push(value) => {
+                // Pushes 0 (or the given value) to the top of the stack
+                let value = in_domain(domain, at, value as i128)?;
+                push(memory, at, value)?;
+            },
+            Op::Pop => {
+                // Pops the top item from the stack.
+                memory.pop().ok_or_else(|| underflow(at, 1))?;
+            },
+            Op::Subtract => {
+                // Subtracts the 2nd item on the stack from the top item and pushes the result to the stack.
+                // The legacy semantics subtracts the top item from the 2nd one instead.
+                let (top, second) = top_two(memory, at)?;
+                let value = in_domain(domain, at, self.config.semantics.subtract(top, second))?;
+                push(memory, at, value)?;
+            },
+            Op::Increment | Op::IncrementBy5 | Op::Decrement | Op::DecrementBy5 => {
+                // Increments/decrements the top item of the stack by 1 (i, d) or 5 (v, w).
+                adjust(memory, domain, self.config.semantics, at)?;
+            },
+            Op::Modulo => {
+                // Pushes the top item mod the 2nd item onto the stack.
+                let (top, second) = top_two(memory, at)?;
+                if second == 0 {
+                    return Err(InterpreterError::DivisionByZero { at });
+                }
+                let value = in_domain(domain, at, top as i128 % second as i128)?;
+                push(memory, at, value)?;
+            },
+            Op::PrintChar => {
+                // Prints the top item on the stack as an ASCII character.
+                // The encoding decides which values are characters and how they are printed.
+                let elem = top(memory, at)?;
+                let encoding = self.config.encoding;
+                let printed = match encoding.encode(elem) {
+                    Some(x) => x,
+                    None => return Err(InterpreterError::NotACharacter { at, value: elem, encoding }),
+                };
+                self.io.write_bytes(&printed).map_err(|err| output_error(at, err))?;
+            },
+            Op::Add => {
+                // Adds the first 2 stack items together and pushes the result to the stack.
+                let (top, second) = top_two(memory, at)?;
+                let value = in_domain(domain, at, second as i128 + top as i128)?;
+                push(memory, at, value)?;
+            },
+            Op::ReadNumber => {
+                // Gets input from the user as a number and pushes to the stack.
+                // Whatever was printed so far must be visible before waiting for input.
+                self.io.flush().map_err(|err| output_error(at, err))?;
+                let mut input = Input {
+                    io: &mut self.io,
+                    unread: &mut self.unread,
+                    consumed: self.history.as_mut().map(|history| &mut history.consumed),
+                };
+                let line = match self.config.number_input {
+                    NumberInput::Line => read_line(&mut input, at)?,
+                    NumberInput::Stream => read_word(&mut input, at)?,
+                };
+                let line = match line {
+                    Some(x) => x,
+                    None => return end_of_input(memory, domain, self.config.eof, at, "number"),
+                };
+                let trimmed = line.trim();
+                match trimmed.parse::<i64>() {
+                    Ok(i) => {
+                        let value = in_domain(domain, at, i as i128)?;
+                        push(memory, at, value)?;
+                    },
+                    Err(_) => {
+                        return Err(input_error(at, format!("input `{}` is not an integer", trimmed)));
+                    }
+                };
+            },
+            Op::ReadChar => {
+                // Gets input from the user as a character and pushes that characters ASCII code onto the stack.
+                // With the utf8 encoding, a character is a whole code point.
+                self.io.flush().map_err(|err| output_error(at, err))?;
+                let mut input = Input {
+                    io: &mut self.io,
+                    unread: &mut self.unread,
+                    consumed: self.history.as_mut().map(|history| &mut history.consumed),
+                };
+                let read_char = match read_char(&mut input, self.config.encoding, at)? {
+                    Some(x) => x,
+                    None => return end_of_input(memory, domain, self.config.eof, at, "char"),
+                };
+                let value = in_domain(domain, at, read_char as i128)?;
+                push(memory, at, value)?;
+            },
+            Op::Swap => {
+                // Swaps the 1st and 2nd items on the stack.
+                top_two(memory, at)?;
+                let len = memory.length();
+                memory.swap(len - 1, len - 2).map_err(|err| stack_error(memory, at, err))?;
+            },
+            Op::Multiply => {
+                // Multiplies the first 2 stack items together and pushes the result onto the stack.
+                let (top, second) = top_two(memory, at)?;
+                let value = in_domain(domain, at, second as i128 * top as i128)?;
+                push(memory, at, value)?;
+            },
+            Op::Equals => {
+                // If the 1st item on the stack is equal to the 2nd item, push a 1 to the stack, else push a 0.
+                let (top, second) = top_two(memory, at)?;
+                let value = in_domain(domain, at, if top == second { 1 } else { 0 })?;
+                push(memory, at, value)?;
+            },
+            Op::RemoveNth => {
+                // Pops the (top item on the stack)th item on the stack.
+                // Note: nth element is from the top of the stack, not the bottom
+                let idx = indexed_item(memory, at)?;
+                memory.remove(idx).map_err(|err| stack_error(memory, at, err))?;
+            },
+            Op::Divide => {
+                // Divides the top item on the stack by the 2nd item and pushes the result onto the stack.
+                let (top, second) = top_two(memory, at)?;
+                if second == 0 {
+                    return Err(InterpreterError::DivisionByZero { at });
+                }
+                let value = in_domain(domain, at, top as i128 / second as i128)?;
+                push(memory, at, value)?;
+            },
+            Op::Duplicate => {
+                // Duplicates the top item on the stack.
+                let elem = top(memory, at)?;
+                push(memory, at, elem)?;
+            },
+            Op::Depth => {
+                // Pushes the total length of the stack onto the stack.
+                let value = in_domain(domain, at, at.depth as i128)?;
+                push(memory, at, value)?;
+            },
+            Op::SwapNth => {
+                // Swaps the 1st and (top item on the stack)th items on the stack.
+                // Note: nth element is from the top of the stack, not the bottom
+                let idx = indexed_item(memory, at)?;
+                let len = memory.length();
+                memory.swap(len - 1, idx).map_err(|err| stack_error(memory, at, err))?;
+            },
+            Op::PrintNumber => {
+                // Prints the top item on the stack as an integer.
+                let printed = top(memory, at)?.to_string();
+                self.io.write_str(&printed).map_err(|err| output_error(at, err))?;
+            },
+            Op::Clear => {
+                // Deletes the entire stack.
+                memory.clear();
+            },
+            Op::ResetCell => {
+                // Cell profile: Add/Overwrite the value in memory by 0
+                let value = in_domain(domain, at, 0)?;
+                memory.clear();
+                push(memory, at, value)?;
+            },
+            Op::EmptyCell => {
+                // Cell profile: Remove the value in memory.
+                memory.pop().ok_or_else(|| underflow(at, 1))?;
+            },
+            Op::SkipIfZero(_) | Op::JumpIfZero(_) | Op::JumpIfNotZero(_) | Op::Halt | Op::AddConst { .. } | Op::Guard { .. } | Op::ZeroTop => {
+                // These change the program index or replace several instructions,
+                // they are executed by `execute` and `run_op`
+            },
+        };
+        Ok(())
+    }
+
+    // Where a `t` or `u` executed at the current index jumps to. The debugger can
+    // execute them anywhere, in that case the matching instruction is searched.
+    fn jump_target(&self, instruction: char) -> usize {
+        let idx = self.curr_prog_idx;
+        if self.program[idx] == instruction {
+            return self.jumps[idx];
+        }
+        match instruction {
+            't' => find_corresponding_u(&self.program, idx),
+            _ => find_corresponding_t(&self.program, idx),
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Cursor;
+use std::io::Stdout;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::TryLockError;
+
+// Where `h` and `j` read from and where `f` and `x` print to.
+pub trait Io {
+    // Reads a line, including its line ending, into `buf`. Returns the number of bytes
+    // read, 0 at the end of the input.
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize>;
+
+    // Reads a single byte, `None` at the end of the input.
+    fn read_byte(&mut self) -> io::Result<Option<u8>>;
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()>;
+
+    fn write_str(&mut self, s: &str) -> io::Result<()> {
+        self.write_bytes(s.as_bytes())
+    }
+
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+// Reads the next byte of a buffered reader.
+fn read_byte_from<R: BufRead>(reader: &mut R) -> io::Result<Option<u8>> {
+    let byte = match reader.fill_buf()?.first() {
+        Some(x) => *x,
+        None => return Ok(None),
+    };
+    reader.consume(1);
+    Ok(Some(byte))
+}
+
+// The standard input and output of the process. The output is buffered unless
+// created with `unbuffered`, the interpreter flushes it before reading input and
+// once `run` returns.
+#[derive(Debug)]
+pub struct StdIo {
+    // Shared with the `StdoutHandle`s, so that another thread can flush it
+    output: Arc<Mutex<BufWriter<Stdout>>>,
+    buffered: bool,
+}
+
+// Locks the output, even if a thread panicked while writing to it.
+fn lock_output(output: &Mutex<BufWriter<Stdout>>) -> MutexGuard<'_, BufWriter<Stdout>> {
+    output.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+impl StdIo {
+    pub fn buffered() -> StdIo {
+        StdIo {
+            output: Arc::new(Mutex::new(BufWriter::new(io::stdout()))),
+            buffered: true,
+        }
+    }
+
+    // Every write reaches the terminal right away, for interactive programs.
+    pub fn unbuffered() -> StdIo {
+        StdIo {
+            output: Arc::new(Mutex::new(BufWriter::with_capacity(0, io::stdout()))),
+            buffered: false,
+        }
+    }
+
+    // A handle to flush the output from another thread, such as a timeout watchdog.
+    pub fn output_handle(&self) -> StdoutHandle {
+        StdoutHandle { output: Arc::clone(&self.output) }
+    }
+}
+
+impl Default for StdIo {
+    fn default() -> StdIo {
+        StdIo::buffered()
+    }
+}
+
+impl Io for StdIo {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        io::stdin().read_line(buf)
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        read_byte_from(&mut io::stdin().lock())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let mut output = lock_output(&self.output);
+        output.write_all(bytes)?;
+        if !self.buffered {
+            output.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        lock_output(&self.output).flush()
+    }
+}
+
+// The output of a `StdIo`, flushed from another thread.
+#[derive(Debug, Clone)]
+pub struct StdoutHandle {
+    output: Arc<Mutex<BufWriter<Stdout>>>,
+}
+
+impl StdoutHandle {
+    // Flushes what the program printed, unless the output is in use: the thread
+    // writing to it may be blocked, waiting on a full pipe. Returns false then.
+    pub fn try_flush(&self) -> bool {
+        let mut output = match self.output.try_lock() {
+            Ok(x) => x,
+            Err(TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(TryLockError::WouldBlock) => return false,
+        };
+        output.flush().is_ok()
+    }
+}
+
+// Scripted input and captured output, to run programs without a terminal.
+#[derive(Debug, Default)]
+pub struct BufferIo {
+    input: Cursor<Vec<u8>>,
+    output: Vec<u8>,
+}
+
+impl BufferIo {
+    pub fn new(input: &str) -> BufferIo {
+        BufferIo::from_bytes(input.as_bytes())
+    }
+
+    // Input that doesn't have to be valid UTF-8.
+    pub fn from_bytes(input: &[u8]) -> BufferIo {
+        BufferIo {
+            input: Cursor::new(input.to_vec()),
+            output: Vec::new(),
+        }
+    }
+
+    // Everything the program printed so far.
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    pub fn output_string(&self) -> String {
+        String::from_utf8_lossy(&self.output).into_owned()
+    }
+}
+
+impl Io for BufferIo {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        self.input.read_line(buf)
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        read_byte_from(&mut self.input)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.output.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Input read from a file and output written to another one.
+#[derive(Debug)]
+pub struct FileIo {
+    input: BufReader<File>,
+    output: File,
+}
+
+impl FileIo {
+    // Opens `input` for reading and creates (or truncates) `output`.
+    pub fn open<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q) -> io::Result<FileIo> {
+        Ok(FileIo {
+            input: BufReader::new(File::open(input)?),
+            output: File::create(output)?,
+        })
+    }
+}
+
+impl Io for FileIo {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        self.input.read_line(buf)
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        read_byte_from(&mut self.input)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.output.write_all(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+// Wraps another `Io` and fails once the program printed more than `limit` bytes.
+// The output is written up to the limit.
+#[derive(Debug)]
+pub struct LimitedIo<I: Io> {
+    inner: I,
+    limit: u64,
+    written: u64,
+    limit_reached: bool,
+}
+
+impl<I: Io> LimitedIo<I> {
+    pub fn new(inner: I, limit: u64) -> LimitedIo<I> {
+        LimitedIo {
+            inner,
+            limit,
+            written: 0,
+            limit_reached: false,
+        }
+    }
+
+    pub fn inner(&self) -> &I {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+
+    // True once the program tried to print past the limit.
+    pub fn limit_reached(&self) -> bool {
+        self.limit_reached
+    }
+}
+
+impl<I: Io> Io for LimitedIo<I> {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        self.inner.read_line(buf)
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        self.inner.read_byte()
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let available = self.limit - self.written;
+        if bytes.len() as u64 <= available {
+            self.written += bytes.len() as u64;
+            return self.inner.write_bytes(bytes);
+        }
+        // Print what still fits
+        let end = available as usize;
+        self.inner.write_bytes(&bytes[..end])?;
+        self.written += end as u64;
+        self.limit_reached = true;
+        Err(io::Error::other(format!("output limit of {} byte(s) reached", self.limit)))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
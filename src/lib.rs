@@ -0,0 +1,52 @@
+// StupidStackLanguage: https://esolangs.org/wiki/StupidStackLanguage
+//
+// The parser turns source text into a program string and its `SourceMap`, the
+// `Interpreter` runs it on a `Stack`, reading and printing through an `Io`, and the
+// `Debugger` wraps an `Interpreter` with an interactive prompt. Errors can be
+// rendered against the source with a `Diagnostic`.
+
+pub mod stack;
+pub mod io;
+pub mod error;
+pub mod source_map;
+pub mod diagnostic;
+pub mod parser;
+pub mod profile;
+pub mod domain;
+pub mod input;
+pub mod encoding;
+pub mod semantics;
+pub mod bytecode;
+pub mod optimizer;
+pub mod condition;
+pub mod history;
+pub mod interpreter;
+pub mod debugger;
+
+pub use crate::stack::Stack;
+pub use crate::stack::StackError;
+pub use crate::io::Io;
+pub use crate::io::StdIo;
+pub use crate::io::StdoutHandle;
+pub use crate::io::BufferIo;
+pub use crate::io::FileIo;
+pub use crate::io::LimitedIo;
+pub use crate::error::InterpreterError;
+pub use crate::error::LoadError;
+pub use crate::error::Position;
+pub use crate::error::UnmatchedLoop;
+pub use crate::diagnostic::Diagnostic;
+pub use crate::parser::Program;
+pub use crate::source_map::SourceMap;
+pub use crate::interpreter::Config;
+pub use crate::interpreter::Interpreter;
+pub use crate::interpreter::Outcome;
+pub use crate::profile::Profile;
+pub use crate::domain::Domain;
+pub use crate::domain::Overflow;
+pub use crate::input::Eof;
+pub use crate::input::NumberInput;
+pub use crate::encoding::Encoding;
+pub use crate::semantics::Semantics;
+pub use crate::condition::Condition;
+pub use crate::debugger::Debugger;
@@ -0,0 +1,287 @@
+mod cli;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::process;
+use std::result::Result;
+use std::thread;
+use std::time::Duration;
+use ssl::parser;
+use ssl::parser::ParseError;
+use ssl::Config;
+use ssl::Debugger;
+use ssl::Diagnostic;
+use ssl::Interpreter;
+use ssl::InterpreterError;
+use ssl::Io;
+use ssl::LimitedIo;
+use ssl::LoadError;
+use ssl::Outcome;
+use ssl::Program;
+use ssl::StdIo;
+use ssl::StdoutHandle;
+use crate::cli::Command;
+use crate::cli::Options;
+use crate::cli::Source;
+
+// Exit codes
+const EXIT_OK: i32 = 0;
+const EXIT_EXECUTION_ERROR: i32 = 1;
+const EXIT_USAGE_ERROR: i32 = 2;
+const EXIT_OUT_OF_FUEL: i32 = 3;
+const EXIT_TIMEOUT: i32 = 4;
+const EXIT_OUTPUT_LIMIT: i32 = 5;
+
+fn load_program(source: &Source) -> Result<Program, ParseError> {
+    match source {
+        Source::Expression(text) => parser::parse_expression("<command line>", text),
+        Source::Path(path) => parser::load_file(path),
+        Source::Stdin => parser::load_reader("<stdin>", io::stdin()),
+    }
+}
+
+fn config(options: &Options) -> Config {
+    Config {
+        max_stack_size: options.stack_size,
+        profile: options.profile,
+        lenient_loops: options.lenient_loops,
+        domain: options.domain,
+        eof: options.eof,
+        number_input: options.number_input,
+        encoding: options.encoding,
+        semantics: options.semantics,
+        optimize: options.optimize,
+        fuel: options.fuel,
+    }
+}
+
+fn report_load_error(err: &LoadError, program: &Program) {
+    let diagnostics = err.diagnostics(&program.source_map);
+    for diagnostic in &diagnostics {
+        eprintln!("{}\n", diagnostic);
+    }
+    eprintln!("error: aborting due to {} previous error(s)", diagnostics.len());
+}
+
+fn report_runtime_error(err: &InterpreterError, program: &Program) {
+    // The program output must come before the error message
+    let _ = io::stdout().flush();
+    let message = err.to_string();
+    eprintln!("\n{}", Diagnostic::at_index(&message, &program.source_map, err.position().index));
+}
+
+// Writes the error and the state the program failed in to `path`.
+fn write_dump<I: Io>(path: &str, err: &InterpreterError, program: &Program, interpreter: &Interpreter<I>) {
+    let location = match program.source_map.location(err.position().index) {
+        Some(x) => format!("{}:{}:{}", program.source_map.name(), x.line, x.column),
+        None => program.source_map.name().to_string(),
+    };
+    let dump = format!("error: {}\nlocation: {}\n{}", err, location, interpreter.dump());
+    if let Err(x) = fs::write(path, dump) {
+        eprintln!("error: cannot write the state dump to {}: {}", path, x);
+    }
+}
+
+// Ends the process once `timeout` elapsed. It runs on its own thread so that it
+// also stops a program blocked waiting for input. What the program printed is
+// flushed first, unless the main thread is blocked writing it.
+fn start_watchdog(timeout: Duration, output: StdoutHandle) {
+    thread::spawn(move || {
+        thread::sleep(timeout);
+        // The main thread only holds the output while it writes, try again a few times
+        for _ in 0..10 {
+            if output.try_flush() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        eprintln!("\nerror: timeout, the program was stopped after {} second(s)", timeout.as_secs_f64());
+        process::exit(EXIT_TIMEOUT);
+    });
+}
+
+fn std_io(options: &Options) -> StdIo {
+    if options.unbuffered {
+        StdIo::unbuffered()
+    } else {
+        StdIo::buffered()
+    }
+}
+
+fn execute(program: &Program, config: Config, options: &Options) -> i32 {
+    let fuel = config.fuel.unwrap_or(0);
+    let std_io = std_io(options);
+    let output = std_io.output_handle();
+    let io = LimitedIo::new(std_io, options.max_output.unwrap_or(u64::MAX));
+    let mut interpreter = match Interpreter::with_io(&program.code, config, io) {
+        Ok(x) => x,
+        Err(err) => {
+            report_load_error(&err, program);
+            return EXIT_USAGE_ERROR;
+        }
+    };
+    if let Some(timeout) = options.timeout {
+        start_watchdog(timeout, output);
+    }
+    let debug = options.debug || options.command == Command::Debug;
+    let result = if debug {
+        let mut debugger = Debugger::new(interpreter);
+        debugger.set_break_on_error(options.break_on_error);
+        debugger.run()
+    } else {
+        let result = match interpreter.run() {
+            Ok(Outcome::Finished) => Ok(()),
+            Ok(Outcome::OutOfFuel) => {
+                let _ = io::stdout().flush();
+                let message = format!("out of fuel, the program was stopped after {} instruction(s)", fuel);
+                eprintln!("\n{}", Diagnostic::at_index(&message, &program.source_map, interpreter.program_index()));
+                return EXIT_OUT_OF_FUEL;
+            },
+            Err(err) => {
+                if let Some(path) = &options.dump {
+                    write_dump(path, &err, program, &interpreter);
+                }
+                Err(err)
+            },
+        };
+        if interpreter.io().limit_reached() {
+            if let Err(err) = result {
+                report_runtime_error(&err, program);
+            }
+            return EXIT_OUTPUT_LIMIT;
+        }
+        result
+    };
+    match result {
+        Ok(_) => {
+            let _ = io::stdout().flush();
+            EXIT_OK
+        },
+        Err(err) => {
+            report_runtime_error(&err, program);
+            EXIT_EXECUTION_ERROR
+        }
+    }
+}
+
+// Reports the instructions that the selected profile ignores
+// and whether the program would be accepted by the interpreter.
+fn check(program: &Program, config: Config) -> i32 {
+    let mut ignored = 0;
+    for (idx, ch) in program.code.chars().enumerate() {
+        if !config.profile.contains(ch) {
+            let location = match program.source_map.location(idx) {
+                Some(x) => format!("{}:{}:{}", program.source_map.name(), x.line, x.column),
+                None => program.source_map.name().to_string(),
+            };
+            println!("warning: {}: `{}` at index {} is not part of the {} profile and will be ignored", location, ch, idx, config.profile);
+            ignored += 1;
+        }
+    }
+    if let Err(err) = Interpreter::new(&program.code, config) {
+        report_load_error(&err, program);
+        return EXIT_USAGE_ERROR;
+    }
+    if ignored == 0 {
+        println!("OK");
+    }
+    EXIT_OK
+}
+
+fn repl(config: Config, io: StdIo) -> i32 {
+    let fuel = config.fuel;
+    let mut interpreter = match Interpreter::with_io("", config, io) {
+        Ok(x) => x,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return EXIT_USAGE_ERROR;
+        }
+    };
+    loop {
+        print!("ssl> ");
+        if io::stdout().flush().is_err() {
+            return EXIT_EXECUTION_ERROR;
+        }
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) => {
+                println!();
+                return EXIT_OK;
+            },
+            Ok(_) => {},
+            Err(x) => {
+                eprintln!("Error: cannot read from stdin: {}", x);
+                return EXIT_EXECUTION_ERROR;
+            }
+        };
+        let program = match parser::parse_expression("<repl>", &line) {
+            Ok(x) => x,
+            Err(err) => {
+                eprintln!("{}", err);
+                continue;
+            }
+        };
+        if let Err(err) = interpreter.load_program(&program.code) {
+            report_load_error(&err, &program);
+            continue;
+        }
+        interpreter.set_fuel(fuel);
+        let result = interpreter.run();
+        println!();
+        match result {
+            Ok(Outcome::Finished) => {},
+            Ok(Outcome::OutOfFuel) => {
+                let message = format!("out of fuel, the line was stopped after {} instruction(s)", fuel.unwrap_or(0));
+                eprintln!("{}", Diagnostic::at_index(&message, &program.source_map, interpreter.program_index()));
+            },
+            Err(err) => {
+                let message = err.to_string();
+                eprintln!("{}", Diagnostic::at_index(&message, &program.source_map, err.position().index));
+            },
+        }
+    }
+}
+
+fn run(options: &Options) -> i32 {
+    let program = match &options.source {
+        Some(source) => match load_program(source) {
+            Ok(x) => x,
+            Err(err) => {
+                eprintln!("{}", err);
+                return EXIT_USAGE_ERROR;
+            }
+        },
+        None => Program {
+            code: String::new(),
+            source_map: ssl::SourceMap::new("<none>", ""),
+        },
+    };
+    let config = config(options);
+    match options.command {
+        Command::Run | Command::File | Command::Debug => execute(&program, config, options),
+        Command::Check => check(&program, config),
+        Command::Fmt => {
+            println!("{}", program.code);
+            EXIT_OK
+        },
+        Command::Repl => repl(config, std_io(options)),
+        Command::Help => {
+            print!("{}", cli::USAGE);
+            EXIT_OK
+        },
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let code = match cli::parse_args(&args) {
+        Ok(options) => run(&options),
+        Err(s) => {
+            eprintln!("Error: {}\n\n{}", s, cli::USAGE);
+            EXIT_USAGE_ERROR
+        }
+    };
+    process::exit(code);
+}
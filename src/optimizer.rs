@@ -0,0 +1,134 @@
+use crate::bytecode::Bytecode;
+use crate::bytecode::Op;
+use crate::bytecode::Span;
+use crate::bytecode;
+use crate::domain::Domain;
+use crate::semantics::Semantics;
+
+// Change of the top item made by an op, and the range the top item must be in
+// for it to succeed.
+fn op_adjustment(op: Op, domain: &Domain, semantics: Semantics) -> Option<(i64, i64, i64)> {
+    match op {
+        Op::Increment => bytecode::adjustment('i', domain, semantics),
+        Op::IncrementBy5 => bytecode::adjustment('v', domain, semantics),
+        Op::Decrement => bytecode::adjustment('d', domain, semantics),
+        Op::DecrementBy5 => bytecode::adjustment('w', domain, semantics),
+        Op::AddConst { delta, min, max } => Some((delta, min, max)),
+        _ => None,
+    }
+}
+
+// The op doing `first` then `second`, if there is one.
+fn fuse_pair(first: Op, second: Op, domain: &Domain, semantics: Semantics) -> Option<Op> {
+    match (first, second) {
+        // A push outside of the domain is left to the overflow policy
+        (Op::Push(value), _) if !domain.contains(value) => None,
+        (Op::Push(_), Op::Pop) => Some(Op::Guard { depth: 0, room: 1 }),
+        // `a` followed by `v`/`i`/...: push the result, if building it can't fail
+        (Op::Push(value), second) => {
+            let (delta, min, max) = op_adjustment(second, domain, semantics)?;
+            if value < min || value > max {
+                return None;
+            }
+            Some(Op::Push(value + delta))
+        },
+        (Op::Duplicate, Op::Pop) => Some(Op::Guard { depth: 1, room: 1 }),
+        (Op::Guard { depth: depth_a, room: room_a }, Op::Guard { depth: depth_b, room: room_b }) => {
+            Some(Op::Guard { depth: depth_a.max(depth_b), room: room_a.max(room_b) })
+        },
+        (first, second) => {
+            // Runs of `v`/`w`/`i`/`d` in any order
+            let (delta_a, min_a, max_a) = op_adjustment(first, domain, semantics)?;
+            let (delta_b, min_b, max_b) = op_adjustment(second, domain, semantics)?;
+            // The top item is `top + delta_a` when the second op runs
+            let min = min_a.max(min_b.saturating_sub(delta_a));
+            let max = max_a.min(max_b.saturating_sub(delta_a));
+            if min > max {
+                // Would always fail, leave it to the unmerged ops
+                return None;
+            }
+            Some(Op::AddConst { delta: delta_a + delta_b, min, max })
+        },
+    }
+}
+
+// Rewrites the bytecode of a program of `len` instructions into equivalent
+// faster bytecode:
+//   `avvvii`  pushes the constant directly,
+//   `vvwid`   is one addition, whatever the order of the instructions,
+//   `qb`      is dropped, only checking that it would not fail,
+//   `tdu`     sets the top item to 0 without looping.
+// Ops are only fused when no jump lands in the middle of them. When a fused op
+// can fail or leave the domain, the interpreter executes its instructions one by
+// one instead, so the errors are the same as without optimizing.
+pub fn optimize(bytecode: &Bytecode, len: usize, domain: &Domain, semantics: Semantics) -> Bytecode {
+    let ops = &bytecode.ops;
+    // How many jumps land on each op
+    let mut jumps_to = vec![0; ops.len() + 1];
+    for op in ops {
+        match op {
+            Op::SkipIfZero(target) | Op::JumpIfZero(target) | Op::JumpIfNotZero(target) => {
+                jumps_to[*target] += 1;
+            },
+            _ => {}
+        }
+    }
+
+    // The optimized ops, their spans and the op they start with in `ops`
+    let mut fused: Vec<(Op, Span, usize)> = Vec::with_capacity(ops.len());
+    for (old_idx, (op, span)) in ops.iter().zip(bytecode.spans.iter()).enumerate() {
+        let mut current = (*op, *span, old_idx);
+        loop {
+            let (last_op, last_span, last_idx) = match fused.last() {
+                Some(x) if jumps_to[current.2] == 0 => *x,
+                _ => break,
+            };
+            let op = match fuse_pair(last_op, current.0, domain, semantics) {
+                Some(x) => x,
+                None => break,
+            };
+            let end = current.1.index + current.1.len;
+            let span = Span { index: last_span.index, len: end - last_span.index };
+            fused.pop();
+            current = (op, span, last_idx);
+        }
+        fused.push(current);
+
+        // `t d u`, where the `u` is the only jump to the `d` and the `t` jumps right after the `u`.
+        // Counting down to 0 only stays in the domain if it includes 0.
+        let count = fused.len();
+        if count >= 3 && domain.min <= 0 {
+            let loop_ops = (fused[count - 3].0, fused[count - 2].0, fused[count - 1].0);
+            let t_idx = fused[count - 3].2;
+            let consecutive = fused[count - 2].2 == t_idx + 1 && fused[count - 1].2 == t_idx + 2;
+            if consecutive
+                && loop_ops == (Op::JumpIfZero(t_idx + 3), Op::Decrement, Op::JumpIfNotZero(t_idx + 1))
+                && jumps_to[t_idx + 1] == 1
+                && jumps_to[t_idx + 2] == 0 {
+                let start = fused[count - 3].1.index;
+                let end = fused[count - 1].1.index + fused[count - 1].1.len;
+                let span = Span { index: start, len: end - start };
+                fused.truncate(count - 3);
+                fused.push((Op::ZeroTop, span, t_idx));
+            }
+        }
+    }
+
+    // Jumps only land on the first op of a fused op, point them to the new indices
+    let mut new_idx = vec![fused.len(); ops.len() + 1];
+    for (idx, (_, _, old_idx)) in fused.iter().enumerate() {
+        new_idx[*old_idx] = idx;
+    }
+    let (ops, spans): (Vec<Op>, Vec<Span>) = fused.into_iter()
+        .map(|(op, span, _)| {
+            let op = match op {
+                Op::SkipIfZero(target) => Op::SkipIfZero(new_idx[target]),
+                Op::JumpIfZero(target) => Op::JumpIfZero(new_idx[target]),
+                Op::JumpIfNotZero(target) => Op::JumpIfNotZero(new_idx[target]),
+                op => op,
+            };
+            (op, span)
+        })
+        .unzip();
+    Bytecode::new(ops, spans, len)
+}
@@ -0,0 +1,226 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::Read;
+use crate::diagnostic::Diagnostic;
+use crate::source_map::Location;
+use crate::source_map::SourceMap;
+
+// Assignment 1:
+// Write a function that takes in a string. Return:
+//     The same string,
+//     All lowercase,
+//     Stripped of whitespaces.
+pub fn cleanup_string(input: &str) -> String {
+    let mut output = input.to_lowercase();
+    output.retain(|ch| !ch.is_whitespace());
+    output
+}
+
+// Assignment 2
+// Write a function that takes in a string. Return:
+//     The same string,
+//     All lowercase,
+//     Stripped of whitespaces,
+//     Without any comments.
+pub fn cleanup_and_strip_comments(input: &str) -> String {
+    cleanup_string(input)
+        .split("//")
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+// Assignment 3
+// Return:
+//     The same string,
+//     All lowercase,
+//     Stripped of whitespaces,
+//     Without any comments.
+// If the final result contains any characters that aren’t part of our language (a-z), throw an error.
+pub fn parse_line(input: &str) -> Result<String, ParseError> {
+    Ok(parse_expression("<input>", input)?.code)
+}
+
+// Assignment 4
+// Write a function that takes in a file name.
+// Return a single string, with each line in the file concatenated. For each line in the file, apply the function that you got from Assignment 3.
+pub fn parse_file(filename: &str) -> Result<String, ParseError> {
+    Ok(load_file(filename)?.code)
+}
+
+// Same as `parse_file`, for source text that is already in memory (e.g. read from stdin).
+pub fn parse_source(source: &str) -> Result<String, ParseError> {
+    Ok(parse_program("<input>", source)?.code)
+}
+
+// Final Assignment
+// Write a function that takes in two strings as arguments. If the first item is equal to “run”, call the function that you wrote in Assignment 3 using the second argument.
+// Else if the first item is equal to “file”, call the function that you wrote in Assignment 4 using the second argument.
+// Else, throw an error.
+pub fn run_or_file(command: &str, cmd_arg: &str) -> Result<String, ParseError> {
+    match command {
+        "run" => {
+            parse_line(cmd_arg)
+        },
+        "file" => {
+            parse_file(cmd_arg)
+        },
+        _ => {
+            Err(ParseError::UnknownCommand(command.to_string()))
+        }
+    }
+}
+
+// A character that is not an instruction, found outside of comments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidCharacter {
+    pub character: char,
+    pub file: String,
+    pub location: Location,
+    pub source_line: String,
+}
+
+impl InvalidCharacter {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic {
+            message: format!("invalid character `{}`", self.character),
+            file: self.file.clone(),
+            location: Some(self.location),
+            source_line: Some(self.source_line.clone()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseError {
+    // The source could not be read.
+    Io { path: String, source: io::Error },
+    // Every invalid character of the source, in order.
+    InvalidCharacters(Vec<InvalidCharacter>),
+    // `run_or_file` got something else than `run` or `file`.
+    UnknownCommand(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Io { path, source } => write!(f, "error: cannot read {}: {}", path, source),
+            ParseError::InvalidCharacters(characters) => {
+                for invalid in characters {
+                    writeln!(f, "{}\n", invalid.to_diagnostic())?;
+                }
+                write!(f, "error: aborting due to {} invalid character(s)", characters.len())
+            },
+            ParseError::UnknownCommand(command) => write!(f, "error: unknown command `{}`", command),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// A parsed program and the source map of its instructions.
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub code: String,
+    pub source_map: SourceMap,
+}
+
+// Same as `cleanup_and_strip_comments`, keeping the location of every character.
+// `input` starts at the given line and may span more lines.
+fn cleanup_with_locations(input: &str, line: usize) -> Vec<(char, Location)> {
+    let mut output = Vec::new();
+    let mut location = Location { line, column: 1 };
+    for ch in input.chars() {
+        if ch == '\n' {
+            location.line += 1;
+            location.column = 1;
+            continue;
+        }
+        if !ch.is_whitespace() {
+            for lower_ch in ch.to_lowercase() {
+                output.push((lower_ch, location));
+            }
+        }
+        location.column += 1;
+    }
+    let comment_start = output.windows(2).position(|pair| pair[0].0 == '/' && pair[1].0 == '/');
+    if let Some(pos) = comment_start {
+        output.truncate(pos);
+    }
+    output
+}
+
+fn append_to_program(program: &mut Program, cleaned: Vec<(char, Location)>, invalid: &mut Vec<InvalidCharacter>) {
+    for (ch, location) in cleaned {
+        if !ch.is_ascii_lowercase() {
+            invalid.push(InvalidCharacter {
+                character: ch,
+                file: program.source_map.name().to_string(),
+                location,
+                source_line: program.source_map.line(location.line).unwrap_or_default().to_string(),
+            });
+            continue;
+        }
+        program.code.push(ch);
+        program.source_map.push(location);
+    }
+}
+
+fn finish_program(program: Program, invalid: Vec<InvalidCharacter>) -> Result<Program, ParseError> {
+    if invalid.is_empty() {
+        Ok(program)
+    } else {
+        Err(ParseError::InvalidCharacters(invalid))
+    }
+}
+
+// Same as `parse_source`, also building the source map of the program.
+// `name` identifies the source in diagnostics, usually it is the path of the file.
+pub fn parse_program(name: &str, source: &str) -> Result<Program, ParseError> {
+    let mut program = Program {
+        code: String::new(),
+        source_map: SourceMap::new(name, source),
+    };
+    let mut invalid = Vec::new();
+    for (idx, line) in source.lines().enumerate() {
+        append_to_program(&mut program, cleanup_with_locations(line, idx + 1), &mut invalid);
+    }
+    finish_program(program, invalid)
+}
+
+// Same as `parse_line`, also building the source map of the program.
+pub fn parse_expression(name: &str, input: &str) -> Result<Program, ParseError> {
+    let mut program = Program {
+        code: String::new(),
+        source_map: SourceMap::new(name, input),
+    };
+    let mut invalid = Vec::new();
+    append_to_program(&mut program, cleanup_with_locations(input, 1), &mut invalid);
+    finish_program(program, invalid)
+}
+
+// Reads and parses the file at `path`.
+pub fn load_file(path: &str) -> Result<Program, ParseError> {
+    match fs::read_to_string(path) {
+        Ok(source) => parse_program(path, &source),
+        Err(source) => Err(ParseError::Io { path: path.to_string(), source }),
+    }
+}
+
+// Reads the whole `reader` and parses it, `name` identifies it in diagnostics.
+pub fn load_reader<R: Read>(name: &str, mut reader: R) -> Result<Program, ParseError> {
+    let mut source = String::new();
+    match reader.read_to_string(&mut source) {
+        Ok(_) => parse_program(name, &source),
+        Err(err) => Err(ParseError::Io { path: name.to_string(), source: err }),
+    }
+}
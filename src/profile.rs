@@ -0,0 +1,60 @@
+use std::fmt;
+use std::str::FromStr;
+
+// Each profile is the instruction set of one of the steps of the challenge.
+// Instructions outside the profile are ignored, as the original interpreters did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Profile {
+    // Part 1: a single memory cell that can be empty.
+    Cell,
+    // Part 2: the first stack instructions.
+    StackBasic,
+    // Part 3: arithmetic, I/O and stack inspection.
+    Arith,
+    // Parts 4 and 5: the whole language, with skips and loops.
+    #[default]
+    Full,
+}
+
+impl Profile {
+    pub const ALL: [Profile; 4] = [Profile::Cell, Profile::StackBasic, Profile::Arith, Profile::Full];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Profile::Cell => "cell",
+            Profile::StackBasic => "stack-basic",
+            Profile::Arith => "arith",
+            Profile::Full => "full",
+        }
+    }
+
+    pub fn instructions(self) -> &'static str {
+        match self {
+            Profile::Cell => "adixyz",
+            Profile::StackBasic => "abdiqvwxyz",
+            Profile::Arith => "abcdefghijlmpqrvwxyz",
+            Profile::Full => "abcdefghijklmnopqrstuvwxyz",
+        }
+    }
+
+    pub fn contains(self, instruction: char) -> bool {
+        self.instructions().contains(instruction)
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Profile, String> {
+        match Profile::ALL.iter().find(|profile| profile.name() == name) {
+            Some(profile) => Ok(*profile),
+            None => Err(format!("unknown profile `{}`", name)),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::domain::Domain;
+use crate::domain::Overflow;
+
+// Which reading of the language to follow where the original interpreter and the
+// published specification (https://esolangs.org/wiki/StupidStackLanguage) disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Semantics {
+    // The `c` and `v`/`w` of the original interpreter: `c` pushes the 2nd item minus the
+    // top item, `v` can't reach the largest value of the domain and `w` the smallest one
+    // (995 to 1000 and 5 to 0 fail by default). The original only checked the results of
+    // `i d v w` against the range, results of `c` are still checked against the domain.
+    #[default]
+    Legacy,
+    // The specification: `c` subtracts the 2nd item from the top item, `v` and `w`
+    // reach every value of the domain like `i` and `d`.
+    Spec,
+}
+
+impl Semantics {
+    pub const ALL: [Semantics; 2] = [Semantics::Legacy, Semantics::Spec];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Semantics::Legacy => "legacy",
+            Semantics::Spec => "spec",
+        }
+    }
+
+    // The value `c` computes, before it is checked against the domain.
+    pub fn subtract(self, top: i64, second: i64) -> i128 {
+        match self {
+            Semantics::Legacy => second as i128 - top as i128,
+            Semantics::Spec => top as i128 - second as i128,
+        }
+    }
+
+    // The values the result of `instruction` must be in. The original interpreter
+    // refused to let `v`/`w` reach the edge of the domain. It had no overflow policy,
+    // so with `wrap` and `saturate` the whole domain is used.
+    pub fn result_domain(self, instruction: char, domain: &Domain) -> Domain {
+        if self == Semantics::Spec || domain.overflow != Overflow::Error {
+            return *domain;
+        }
+        match instruction {
+            'v' => Domain { max: domain.max.saturating_sub(1), ..*domain },
+            'w' => Domain { min: domain.min.saturating_add(1), ..*domain },
+            _ => *domain,
+        }
+    }
+}
+
+impl fmt::Display for Semantics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Semantics {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Semantics, String> {
+        match Semantics::ALL.iter().find(|semantics| semantics.name() == name) {
+            Some(semantics) => Ok(*semantics),
+            None => Err(format!("unknown semantics `{}`", name)),
+        }
+    }
+}
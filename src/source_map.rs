@@ -0,0 +1,47 @@
+// Line and column of a character in the source text, both starting from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+// Maps each instruction of a parsed program back to the source it comes from.
+#[derive(Debug, Clone)]
+pub struct SourceMap {
+    name: String,
+    lines: Vec<String>,
+    locations: Vec<Location>,
+}
+
+impl SourceMap {
+    pub fn new(name: &str, source: &str) -> SourceMap {
+        SourceMap {
+            name: name.to_string(),
+            lines: source.lines().map(|line| line.to_string()).collect(),
+            locations: Vec::new(),
+        }
+    }
+
+    // Records the location of the next instruction of the program.
+    pub fn push(&mut self, location: Location) {
+        self.locations.push(location);
+    }
+
+    // Name of the source: a path, or a placeholder such as `<stdin>`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Location of the instruction at `index` in the program.
+    pub fn location(&self, index: usize) -> Option<Location> {
+        self.locations.get(index).copied()
+    }
+
+    // Text of the given source line, as it was before parsing.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        if line == 0 {
+            return None;
+        }
+        self.lines.get(line - 1).map(|x| x.as_str())
+    }
+}
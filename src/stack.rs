@@ -1,22 +1,48 @@
+use std::fmt;
 use std::vec::Vec;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackError {
+    // The stack already holds `max_size` items.
+    Overflow,
+    // The index does not point to an item of the stack.
+    IndexOutOfRange(usize),
+}
+
+impl fmt::Display for StackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StackError::Overflow => write!(f, "max size reached"),
+            StackError::IndexOutOfRange(idx) => write!(f, "index {} out of range", idx),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Stack<T> {
     stack: Vec<T>,
-    max_size: usize
+    // `None` for a stack that grows as long as there is memory
+    max_size: Option<usize>
 }
 
 impl<T> Stack<T> {
     pub fn new(max_size: usize) -> Stack<T> {
         Stack {
             stack: Vec::new(),
-            max_size: max_size
+            max_size: Some(max_size)
+        }
+    }
+
+    pub fn unbounded() -> Stack<T> {
+        Stack {
+            stack: Vec::new(),
+            max_size: None
         }
     }
 
-    pub fn push(&mut self, elem: T) -> Result<(), String> {
-        if self.stack.len() == self.max_size {
-            return Err(String::from("push error: max size reached"));
+    pub fn push(&mut self, elem: T) -> Result<(), StackError> {
+        if !self.has_room(1) {
+            return Err(StackError::Overflow);
         }
         self.stack.push(elem);
         Ok(())
@@ -30,6 +56,14 @@ impl<T> Stack<T> {
         self.stack.last()
     }
 
+    // The nth item from the top of the stack, the top item being the 0th.
+    pub fn peek(&self, n: usize) -> Option<&T> {
+        if n >= self.stack.len() {
+            return None;
+        }
+        self.stack.get(self.stack.len() - 1 - n)
+    }
+
     pub fn last_mut(&mut self) -> Option<&mut T> {
         self.stack.last_mut()
     }
@@ -42,20 +76,61 @@ impl<T> Stack<T> {
         self.stack.len()
     }
 
-    pub fn swap(&mut self, a: usize, b: usize) -> Result<(), String> {
+    // The items, bottom first.
+    pub fn items(&self) -> &[T] {
+        &self.stack
+    }
+
+    // Replaces every item. The size limit isn't checked, this restores an earlier
+    // state of the stack.
+    pub fn replace(&mut self, items: Vec<T>) {
+        self.stack = items;
+    }
+
+    pub fn max_size(&self) -> Option<usize> {
+        self.max_size
+    }
+
+    // True if `items` more items can be pushed.
+    pub fn has_room(&self, items: usize) -> bool {
+        match self.max_size {
+            Some(max_size) => self.stack.len() + items <= max_size,
+            None => true,
+        }
+    }
+
+    pub fn swap(&mut self, a: usize, b: usize) -> Result<(), StackError> {
         if a >= self.stack.len() {
-            return Err(String::from("swap error: first index out of range"));
+            return Err(StackError::IndexOutOfRange(a));
         }
         if b >= self.stack.len() {
-            return Err(String::from("swap error: second index out of range"));
+            return Err(StackError::IndexOutOfRange(b));
         }
         self.stack.swap(a, b);
         Ok(())
     }
 
-    pub fn remove(&mut self, idx: usize) -> Result<(), String> {
+    pub fn set(&mut self, idx: usize, elem: T) -> Result<(), StackError> {
+        match self.stack.get_mut(idx) {
+            Some(x) => *x = elem,
+            None => return Err(StackError::IndexOutOfRange(idx)),
+        }
+        Ok(())
+    }
+
+    // Puts `elem` at `idx`, moving the items above it up. Like `replace`, the size
+    // limit isn't checked, this puts back an item that was removed.
+    pub fn insert(&mut self, idx: usize, elem: T) -> Result<(), StackError> {
+        if idx > self.stack.len() {
+            return Err(StackError::IndexOutOfRange(idx));
+        }
+        self.stack.insert(idx, elem);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, idx: usize) -> Result<(), StackError> {
         if idx >= self.stack.len() {
-            return Err(String::from("remove error: index out of range"));
+            return Err(StackError::IndexOutOfRange(idx));
         }
         self.stack.remove(idx);
         Ok(())
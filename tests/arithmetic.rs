@@ -0,0 +1,159 @@
+// Differential tests of the arithmetic instructions `c e g m p n`: each one is run on
+// pairs of boundary values, in several domains, and compared to a reference model.
+// Every way of running a program (bytecode, optimized or not, and `step`) must agree
+// with the model, and none of them may panic.
+
+mod common;
+
+use ssl::BufferIo;
+use ssl::Config;
+use ssl::Domain;
+use ssl::Interpreter;
+use ssl::InterpreterError;
+use ssl::Overflow;
+use crate::common::optimized_and_not;
+use crate::common::step_to_end;
+
+const INSTRUCTIONS: [char; 6] = ['c', 'e', 'g', 'm', 'p', 'n'];
+
+const VALUES: [i64; 16] = [
+    i64::MIN, i64::MIN + 1, -1001, -1000, -2, -1, 0, 1, 2, 7, 999, 1000, 1001, i64::MAX - 1, i64::MAX, -7,
+];
+
+fn domains() -> Vec<Domain> {
+    let mut domains = Vec::new();
+    let ranges = [(0, 1000), (-1000, 1000), (-7, 7), (1, 1), (i64::MIN, i64::MAX), (i64::MIN, 0), (0, i64::MAX)];
+    for (min, max) in ranges.iter() {
+        for overflow in Overflow::ALL.iter() {
+            domains.push(Domain { min: *min, max: *max, overflow: *overflow });
+        }
+    }
+    domains
+}
+
+// What an instruction does, as seen from outside of the interpreter.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Pushed(i64),
+    OutOfRange(i128),
+    DivisionByZero,
+}
+
+// The reference model: the result computed without overflow, then fitted into the domain.
+fn model(instruction: char, second: i64, top: i64, domain: &Domain) -> Outcome {
+    let (second, top) = (second as i128, top as i128);
+    let value = match instruction {
+        'c' => second - top,
+        'e' if second == 0 => return Outcome::DivisionByZero,
+        'e' => top % second,
+        'g' => second + top,
+        'm' => second * top,
+        'p' if second == 0 => return Outcome::DivisionByZero,
+        'p' => top / second,
+        'n' => (top == second) as i128,
+        _ => unreachable!(),
+    };
+    let (min, max) = (domain.min as i128, domain.max as i128);
+    if value >= min && value <= max {
+        return Outcome::Pushed(value as i64);
+    }
+    match domain.overflow {
+        Overflow::Error => Outcome::OutOfRange(value),
+        Overflow::Wrap => Outcome::Pushed((min + (value - min).rem_euclid(max - min + 1)) as i64),
+        Overflow::Saturate => Outcome::Pushed(if value < min { min } else { max } as i64),
+    }
+}
+
+// Runs `program` with `input` in the three ways, checks that they agree and returns the
+// result and the stack.
+fn run_all_ways(program: &str, input: &str, domain: Domain) -> (Result<(), InterpreterError>, Vec<i64>) {
+    let config = Config { domain, ..Config::default() };
+    let (result, stack, _) = optimized_and_not(program, &config, |config| common::run_with(program, input, config));
+    let mut interpreter = Interpreter::with_io(program, config, BufferIo::new(input)).unwrap();
+    let stepped = step_to_end(&mut interpreter);
+    let ran = (result, stack);
+    assert_eq!(ran, (stepped, interpreter.memory().items().to_vec()), "run and step of {:?} differ", program);
+    ran
+}
+
+#[test]
+fn arithmetic_matches_the_model_on_boundary_values() {
+    for domain in domains() {
+        let values: Vec<i64> = VALUES.iter().copied().filter(|x| domain.contains(*x)).collect();
+        for instruction in INSTRUCTIONS.iter() {
+            let program = format!("hh{}", instruction);
+            for second in values.iter() {
+                for top in values.iter() {
+                    let input = format!("{}\n{}\n", second, top);
+                    let (result, stack) = run_all_ways(&program, &input, domain);
+                    let context = format!("{} {} {} in {:?}", second, instruction, top, domain);
+                    match model(*instruction, *second, *top, &domain) {
+                        Outcome::Pushed(value) => {
+                            assert_eq!(result, Ok(()), "{}", context);
+                            assert_eq!(stack, vec![*second, *top, value], "{}", context);
+                        },
+                        Outcome::OutOfRange(value) => {
+                            match result {
+                                Err(InterpreterError::ValueOutOfRange { at, value: reported, min, max }) => {
+                                    assert_eq!((at.instruction, at.index), (*instruction, 2), "{}", context);
+                                    assert_eq!((reported, min, max), (value, domain.min, domain.max), "{}", context);
+                                },
+                                other => panic!("{}: expected an out of range error, got {:?}", context, other),
+                            }
+                            assert_eq!(stack, vec![*second, *top], "{}", context);
+                        },
+                        Outcome::DivisionByZero => {
+                            match result {
+                                Err(InterpreterError::DivisionByZero { at }) => {
+                                    assert_eq!((at.instruction, at.index, at.depth), (*instruction, 2, 2), "{}", context);
+                                },
+                                other => panic!("{}: expected a division by zero, got {:?}", context, other),
+                            }
+                            assert_eq!(stack, vec![*second, *top], "{}", context);
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn e_by_zero_reports_a_division_by_zero_like_p() {
+    for instruction in ['e', 'p'].iter() {
+        let program = format!("aaiii{}", instruction);
+        let (result, _) = run_all_ways(&program, "", Domain::default());
+        let message = result.unwrap_err().to_string();
+        assert_eq!(message, format!("`{}` at index 5: dividing by zero", instruction));
+    }
+}
+
+#[test]
+fn arithmetic_needs_two_items() {
+    for instruction in INSTRUCTIONS.iter() {
+        for depth in 0..2 {
+            let program = format!("{}{}", "a".repeat(depth), instruction);
+            match run_all_ways(&program, "", Domain::default()).0 {
+                Err(InterpreterError::StackUnderflow { at, needed }) => {
+                    assert_eq!((at.instruction, at.depth, needed), (*instruction, depth, 2));
+                },
+                other => panic!("{}: expected a stack underflow, got {:?}", program, other),
+            }
+        }
+    }
+}
+
+#[test]
+fn arithmetic_results_overflow_a_full_stack() {
+    let config = Config { max_stack_size: Some(2), ..Config::default() };
+    for instruction in INSTRUCTIONS.iter() {
+        let program = format!("aiai{}", instruction);
+        let mut interpreter = Interpreter::with_io(&program, config.clone(), BufferIo::new("")).unwrap();
+        match interpreter.run() {
+            Err(InterpreterError::StackOverflow { at, max_size }) => {
+                assert_eq!((at.instruction, max_size), (*instruction, 2));
+            },
+            other => panic!("{}: expected a stack overflow, got {:?}", program, other),
+        }
+    }
+}
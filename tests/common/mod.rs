@@ -0,0 +1,47 @@
+// Helpers shared by the integration tests. Each test file uses only some of them.
+#![allow(dead_code)]
+
+use std::fmt::Debug;
+use ssl::BufferIo;
+use ssl::Config;
+use ssl::Interpreter;
+use ssl::InterpreterError;
+use ssl::Io;
+use ssl::Position;
+
+// Runs `program` on `input` and returns the result, the stack, bottom first, and the output.
+pub fn run_with(program: &str, input: impl AsRef<[u8]>, config: Config) -> (Result<(), InterpreterError>, Vec<i64>, Vec<u8>) {
+    let mut interpreter = Interpreter::with_io(program, config, BufferIo::from_bytes(input.as_ref())).unwrap();
+    let result = interpreter.run().map(|_| ());
+    let stack = interpreter.memory().items().to_vec();
+    (result, stack, interpreter.into_io().output().to_vec())
+}
+
+// Calls `step` until the program ends or fails.
+pub fn step_to_end<I: Io>(interpreter: &mut Interpreter<I>) -> Result<(), InterpreterError> {
+    while !interpreter.is_finished() {
+        interpreter.step()?;
+    }
+    Ok(())
+}
+
+// Calls `run` with the optimizer on, then off, checks that both returned the same
+// and returns it.
+pub fn optimized_and_not<T: PartialEq + Debug>(program: &str, config: &Config, run: impl Fn(Config) -> T) -> T {
+    let optimized = run(Config { optimize: true, ..config.clone() });
+    let unoptimized = run(Config { optimize: false, ..config.clone() });
+    assert_eq!(optimized, unoptimized, "optimized and unoptimized runs of {:?} differ", program);
+    optimized
+}
+
+pub fn at(instruction: char, index: usize, depth: usize) -> Position {
+    Position { instruction, index, depth }
+}
+
+pub fn input_error(instruction: char, index: usize, depth: usize, reason: &str) -> InterpreterError {
+    InterpreterError::InputError { at: at(instruction, index, depth), reason: reason.to_string() }
+}
+
+pub fn index_error(instruction: char, index: usize, depth: usize, value: i64) -> InterpreterError {
+    InterpreterError::IndexOutOfRange { at: at(instruction, index, depth), index: value }
+}
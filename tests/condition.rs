@@ -0,0 +1,73 @@
+// Parsing and evaluating the conditions of conditional breakpoints, such as
+// `top == 0` or `stack[2] <= depth`.
+
+use ssl::condition::Comparison;
+use ssl::condition::Operand;
+use ssl::Condition;
+use ssl::Stack;
+
+fn parse(text: &str) -> Result<Condition, String> {
+    text.parse()
+}
+
+fn condition(left: Operand, comparison: Comparison, right: Operand) -> Condition {
+    Condition { left, comparison, right }
+}
+
+// A stack holding `items`, bottom first.
+fn stack(items: &[i64]) -> Stack<i64> {
+    let mut stack = Stack::unbounded();
+    for item in items {
+        stack.push(*item).unwrap();
+    }
+    stack
+}
+
+#[test]
+fn two_character_operators_are_not_read_as_one_character() {
+    assert_eq!(parse("top <= 3"), Ok(condition(Operand::Top, Comparison::LessOrEqual, Operand::Value(3))));
+    assert_eq!(parse("top >= 3"), Ok(condition(Operand::Top, Comparison::GreaterOrEqual, Operand::Value(3))));
+    assert_eq!(parse("top < 3"), Ok(condition(Operand::Top, Comparison::Less, Operand::Value(3))));
+    assert_eq!(parse("top > 3"), Ok(condition(Operand::Top, Comparison::Greater, Operand::Value(3))));
+    assert_eq!(parse("top != 3"), Ok(condition(Operand::Top, Comparison::NotEqual, Operand::Value(3))));
+    assert_eq!(parse("top == 3"), Ok(condition(Operand::Top, Comparison::Equal, Operand::Value(3))));
+}
+
+#[test]
+fn spaces_around_the_operator_are_optional() {
+    assert_eq!(parse("depth>50"), Ok(condition(Operand::Depth, Comparison::Greater, Operand::Value(50))));
+    assert_eq!(parse("-1<=top"), Ok(condition(Operand::Value(-1), Comparison::LessOrEqual, Operand::Top)));
+}
+
+#[test]
+fn stack_slots_count_from_the_bottom() {
+    let parsed = parse("stack[2] == stack[ 0 ]").unwrap();
+    assert_eq!(parsed, condition(Operand::Slot(2), Comparison::Equal, Operand::Slot(0)));
+    assert!(parsed.holds(&stack(&[7, 1, 7])));
+    assert!(!parsed.holds(&stack(&[7, 1, 8])));
+}
+
+#[test]
+fn a_missing_item_makes_the_condition_false() {
+    assert!(!parse("stack[3] == 0").unwrap().holds(&stack(&[0, 0, 0])));
+    assert!(!parse("top == 0").unwrap().holds(&stack(&[])));
+    assert!(!parse("top != 0").unwrap().holds(&stack(&[])));
+    assert!(parse("depth == 0").unwrap().holds(&stack(&[])));
+}
+
+#[test]
+fn bad_operands_are_rejected() {
+    assert_eq!(parse("stack[-1] == 0"), Err(String::from("invalid stack slot `-1`")));
+    assert_eq!(parse("stack[x] == 0"), Err(String::from("invalid stack slot `x`")));
+    assert_eq!(parse("bottom == 0"), Err(String::from("unknown operand `bottom`, expected top, depth, stack[<n>] or an integer")));
+    assert_eq!(parse("top == "), Err(String::from("unknown operand ``, expected top, depth, stack[<n>] or an integer")));
+    assert_eq!(parse("top = 0"), Err(String::from("invalid condition `top = 0`, expected <operand> <comparison> <operand>")));
+}
+
+#[test]
+fn conditions_print_as_they_are_written() {
+    for text in ["top <= 3", "depth > 50", "stack[2] == 65", "-1 != top"].iter() {
+        assert_eq!(parse(text).unwrap().to_string(), *text);
+    }
+    assert_eq!(parse("depth>50").unwrap().to_string(), "depth > 50");
+}
@@ -0,0 +1,220 @@
+// Conformance suite: small programs with the output they must print under both
+// semantics. The spec column follows the published specification
+// (https://esolangs.org/wiki/StupidStackLanguage), the legacy column the `c` and the
+// `v`/`w` edges of the original interpreter. They only disagree on those. Results are
+// checked against the domain in both: the original interpreter only checked `i d v w`,
+// its results of `c`, `g` and `m` need an unbounded domain.
+
+mod common;
+
+use ssl::parser;
+use ssl::Config;
+use ssl::Domain;
+use ssl::Overflow;
+use ssl::Semantics;
+use crate::common::optimized_and_not;
+
+// What a program prints, and the error it stops with if it fails.
+#[derive(Debug, PartialEq)]
+struct Expected {
+    output: String,
+    error: Option<String>,
+}
+
+fn prints(output: &str) -> Expected {
+    Expected { output: output.to_string(), error: None }
+}
+
+fn fails(output: &str, error: &str) -> Expected {
+    Expected { output: output.to_string(), error: Some(error.to_string()) }
+}
+
+struct Case {
+    program: String,
+    input: &'static str,
+    legacy: Expected,
+    spec: Expected,
+}
+
+// A program behaving the same way under both semantics.
+fn same(program: &str, input: &'static str, expected: &str) -> Case {
+    Case { program: program.to_string(), input, legacy: prints(expected), spec: prints(expected) }
+}
+
+fn cases() -> Vec<Case> {
+    vec![
+        same("ax", "", "0"),
+        same("aaibx", "", "0"),
+        // `c` with 2 then 5 on the stack
+        Case {
+            program: String::from("aiiav c x"),
+            input: "",
+            legacy: fails("", "`c` at index 6: value -3 is out of range, values should stay between 0 and 1000"),
+            spec: prints("3"),
+        },
+        // `c` with 5 then 2 on the stack
+        Case {
+            program: String::from("avaii c x"),
+            input: "",
+            legacy: prints("3"),
+            spec: fails("", "`c` at index 6: value -3 is out of range, values should stay between 0 and 1000"),
+        },
+        // `c` keeps its operands
+        Case {
+            program: String::from("avaii c xbxbx"),
+            input: "",
+            legacy: prints("325"),
+            spec: fails("", "`c` at index 6: value -3 is out of range, values should stay between 0 and 1000"),
+        },
+        same("aiid x", "", "1"),
+        // 11 mod 2
+        same("aiiavvi e x", "", "1"),
+        same(&format!("a{}f", "v".repeat(13)), "", "A"),
+        same("avaii g x", "", "7"),
+        same("hx", "42\n", "42"),
+        same("aiiix", "", "3"),
+        same("jx", "A", "65"),
+        same("akix", "", "0"),
+        same("aikix", "", "2"),
+        same("aav l xbx", "", "05"),
+        same("aviiav m x", "", "35"),
+        same("avav n x", "", "1"),
+        same("avai n x", "", "0"),
+        // Removes the 6, the 1st item below the index
+        same("av avi ai o xbx", "", "15"),
+        // 10 divided by 2
+        same("aiiavv p x", "", "5"),
+        same("avq g x", "", "10"),
+        same("aaa r x", "", "3"),
+        // Swaps the index with the 5, the 2nd item below it
+        same("av avi aii s xbxbx", "", "562"),
+        same("aiii txdu", "", "321"),
+        same("aaay r x", "", "0"),
+        same("axzax", "", "0"),
+        // `v` and `w` right before the edges
+        same(&format!("a{}iiii v x", "v".repeat(198)), "", "999"),
+        same("avi w x", "", "1"),
+        // `v` from 995 to 1000
+        Case {
+            program: format!("a{}v x", "v".repeat(199)),
+            input: "",
+            legacy: fails("", "`v` at index 200: value 1000 is out of range, values should stay between 0 and 999"),
+            spec: prints("1000"),
+        },
+        // `w` from 5 to 0
+        Case {
+            program: String::from("avw x"),
+            input: "",
+            legacy: fails("", "`w` at index 2: value 0 is out of range, values should stay between 1 and 1000"),
+            spec: prints("0"),
+        },
+        // The edges also stop merged runs of `v`/`w`
+        Case {
+            program: format!("a{}vvvvvx", "v".repeat(195)),
+            input: "",
+            legacy: fails("", "`v` at index 200: value 1000 is out of range, values should stay between 0 and 999"),
+            spec: prints("1000"),
+        },
+        Case {
+            program: String::from("aiax avvvvwwww x"),
+            input: "",
+            legacy: fails("0", "`w` at index 13: value 0 is out of range, values should stay between 1 and 1000"),
+            spec: prints("00"),
+        },
+    ]
+}
+
+// Runs `program` with and without the optimizer, checks that both agree and returns
+// what it printed and its error.
+fn run(program: &str, input: &str, config: Config) -> Expected {
+    optimized_and_not(program, &config, |config| {
+        let (result, _, output) = common::run_with(program, input, config);
+        Expected { output: String::from_utf8_lossy(&output).into_owned(), error: result.err().map(|err| err.to_string()) }
+    })
+}
+
+fn with_semantics(semantics: Semantics) -> Config {
+    Config { semantics, ..Config::default() }
+}
+
+#[test]
+fn programs_match_the_legacy_semantics() {
+    for case in cases() {
+        let result = run(&case.program, case.input, with_semantics(Semantics::Legacy));
+        assert_eq!(result, case.legacy, "{}", case.program);
+    }
+}
+
+#[test]
+fn programs_match_the_spec_semantics() {
+    for case in cases() {
+        let result = run(&case.program, case.input, with_semantics(Semantics::Spec));
+        assert_eq!(result, case.spec, "{}", case.program);
+    }
+}
+
+#[test]
+fn legacy_is_the_default_semantics() {
+    for case in cases() {
+        assert_eq!(run(&case.program, case.input, Config::default()), case.legacy, "{}", case.program);
+    }
+}
+
+#[test]
+fn hello_world_is_the_same_under_both_semantics() {
+    let program = parser::parse_program("hello_world", include_str!("../src/part_3_hello_world.txt")).unwrap();
+    for semantics in Semantics::ALL.iter() {
+        let result = run(&program.code, "", with_semantics(*semantics));
+        assert_eq!(result, prints("Hello World!\u{c}\r"), "{}", semantics);
+    }
+}
+
+// The original interpreter had no overflow policy, `v` and `w` reach the edges
+// with `wrap` and `saturate` whatever the semantics.
+#[test]
+fn legacy_edges_only_apply_to_the_error_policy() {
+    for overflow in [Overflow::Wrap, Overflow::Saturate].iter() {
+        let domain = Domain { overflow: *overflow, ..Domain::default() };
+        for semantics in Semantics::ALL.iter() {
+            let config = Config { domain, semantics: *semantics, ..Config::default() };
+            let program = format!("a{}v x avw x", "v".repeat(199));
+            assert_eq!(run(&program, "", config), prints("10000"), "{} {}", overflow, semantics);
+        }
+    }
+}
+
+// What the original interpreter printed where `c`, `g` and `m` leave the default range.
+#[test]
+fn legacy_matches_the_original_results_in_an_unbounded_domain() {
+    let domain = Domain { min: i64::MIN, max: i64::MAX, ..Domain::default() };
+    let legacy = Config { domain, ..Config::default() };
+    // `c` with 2 then 5 on the stack, the operands are kept
+    assert_eq!(run("aiiav c xbxbx", "", legacy.clone()), prints("-352"));
+    assert_eq!(run("avaii c x", "", legacy.clone()), prints("3"));
+    // 999 + 999 and 500 × 500
+    assert_eq!(run(&format!("a{}iiii q g x", "v".repeat(199)), "", legacy.clone()), prints("1998"));
+    assert_eq!(run(&format!("a{} q m x", "v".repeat(100)), "", legacy.clone()), prints("250000"));
+    // A negative difference feeds the next instructions: -2 + 2 and -2 × -2
+    assert_eq!(run("aaiic g x", "", legacy.clone()), prints("0"));
+    assert_eq!(run("aaiic q m x", "", legacy), prints("4"));
+}
+
+#[test]
+fn legacy_edges_follow_the_domain() {
+    let domain = Domain { min: -10, max: 10, ..Domain::default() };
+    let legacy = Config { domain, ..Config::default() };
+    assert_eq!(
+        run("aw x", "", legacy.clone()),
+        prints("-5"),
+    );
+    assert_eq!(
+        run("aww x", "", legacy.clone()),
+        fails("", "`w` at index 2: value -10 is out of range, values should stay between -9 and 10"),
+    );
+    assert_eq!(
+        run("avv x", "", legacy),
+        fails("", "`v` at index 2: value 10 is out of range, values should stay between -10 and 9"),
+    );
+    let spec = Config { domain, semantics: Semantics::Spec, ..Config::default() };
+    assert_eq!(run("aww xvvvv x", "", spec), prints("-1010"));
+}
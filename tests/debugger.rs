@@ -0,0 +1,255 @@
+// The debugger driven by scripted commands: breakpoint management, where conditional
+// breakpoints and watchpoints stop the program, prompting on errors with `--break-on-error`
+// and what the debugger prints on the way.
+
+use ssl::BufferIo;
+use ssl::Config;
+use ssl::Debugger;
+use ssl::Domain;
+use ssl::Interpreter;
+use ssl::InterpreterError;
+use ssl::Overflow;
+use ssl::Position;
+
+// A debugger for `program` that reads the commands of `script`, one per line.
+fn debugger(program: &str, script: &str, config: Config) -> Debugger<BufferIo, BufferIo> {
+    let interpreter = Interpreter::with_io(program, config, BufferIo::new("")).unwrap();
+    Debugger::with_console(interpreter, BufferIo::new(script))
+}
+
+// Runs the session. Returns the result and the lines the debugger printed, without the
+// prompts and the empty lines.
+fn run(mut debugger: Debugger<BufferIo, BufferIo>) -> (Result<(), InterpreterError>, Vec<String>) {
+    let result = debugger.run();
+    let transcript = debugger.console().output_string();
+    let lines = transcript.lines()
+        .map(|line| line.trim_start_matches("debug> ").to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    (result, lines)
+}
+
+fn debug_with(program: &str, script: &str, config: Config) -> (Result<(), InterpreterError>, Vec<String>) {
+    run(debugger(program, script, config))
+}
+
+fn debug(program: &str, script: &str) -> (Result<(), InterpreterError>, Vec<String>) {
+    debug_with(program, script, Config::default())
+}
+
+fn break_on_error(program: &str, script: &str) -> (Result<(), InterpreterError>, Vec<String>) {
+    let mut debugger = debugger(program, script, Config::default());
+    debugger.set_break_on_error(true);
+    run(debugger)
+}
+
+// Counts down from 3: the `d` at index 5 runs 3 times.
+const LOOP: &str = "aiiitdu x";
+
+#[test]
+fn a_duplicate_breakpoint_is_rejected() {
+    // Even as a temporary breakpoint
+    let (result, lines) = debug(LOOP, "brk 5\nbrk 5\ntbreak 5\nquit\n");
+    assert_eq!(result, Ok(()));
+    assert_eq!(lines, vec![
+        "Breakpoint 1 set at 5",
+        "Error: breakpoint 1 is already set at 5",
+        "Error: breakpoint 1 is already set at 5",
+    ]);
+}
+
+#[test]
+fn breakpoint_numbers_are_not_reused() {
+    let (_, lines) = debug(LOOP, "brk 4\nbrk 5\ndelete 2\nbrk 6\nclear\nbrk 5\ninfo breaks\nquit\n");
+    assert_eq!(lines, vec![
+        "Breakpoint 1 set at 4",
+        "Breakpoint 2 set at 5",
+        "Breakpoint 2 deleted",
+        "Breakpoint 3 set at 6",
+        "Deleted 2 breakpoint(s)",
+        "Breakpoint 4 set at 5",
+        "Num   Type        Enabled  Hits   Where",
+        "4     breakpoint  yes      0      at 5",
+    ]);
+}
+
+#[test]
+fn a_temporary_breakpoint_is_deleted_after_one_hit() {
+    let (result, lines) = debug(LOOP, "tbreak 5\ncont\npidx\ninfo breaks\ncont\n");
+    assert_eq!(result, Ok(()));
+    assert_eq!(lines, vec![
+        "Temporary breakpoint 1 set at 5",
+        "Temporary breakpoint 1 hit at 5, deleted",
+        "Program index: 5",
+        "No breakpoints",
+    ]);
+}
+
+#[test]
+fn a_disabled_breakpoint_does_not_stop() {
+    let (result, lines) = debug(LOOP, "brk 5\ndisable 1\ncont\n");
+    assert_eq!(result, Ok(()));
+    assert_eq!(lines, vec!["Breakpoint 1 set at 5", "Breakpoint 1 disabled"]);
+
+    let (_, lines) = debug(LOOP, "brk 5\ndisable 1\nenable 1\ncont\npidx\nquit\n");
+    assert_eq!(lines[2..], ["Breakpoint 1 enabled", "Breakpoint 1 hit at 5 (1 time(s))", "Program index: 5"]);
+}
+
+#[test]
+fn hits_are_counted() {
+    let (result, lines) = debug(LOOP, "brk 5\ncont\ncont\ncont\ninfo breaks\ncont\n");
+    assert_eq!(result, Ok(()));
+    assert_eq!(lines, vec![
+        "Breakpoint 1 set at 5",
+        "Breakpoint 1 hit at 5 (1 time(s))",
+        "Breakpoint 1 hit at 5 (2 time(s))",
+        "Breakpoint 1 hit at 5 (3 time(s))",
+        "Num   Type        Enabled  Hits   Where",
+        "1     breakpoint  yes      3      at 5",
+    ]);
+}
+
+#[test]
+fn breakpoint_commands_report_bad_numbers() {
+    let (_, lines) = debug(LOOP, "brk 9\nbrk x\ndelete 1\ndisable\nenable x\nquit\n");
+    assert_eq!(lines, vec![
+        "Error: breakpoint out of range",
+        "Error: breakpoint should be a positive number",
+        "Error: no breakpoint number 1",
+        "Invalid command",
+        "Error: breakpoint number should be a positive number",
+    ]);
+}
+
+#[test]
+fn the_program_output_goes_to_its_own_io() {
+    let interpreter = Interpreter::with_io("aiix", Config::default(), BufferIo::new("")).unwrap();
+    let mut debugger = Debugger::with_console(interpreter, BufferIo::new("cont\n"));
+    assert_eq!(debugger.run(), Ok(()));
+    assert_eq!(debugger.interpreter().io().output_string(), "2");
+    assert!(!debugger.console().output_string().contains('2'));
+}
+
+#[test]
+fn the_end_of_the_commands_stops_the_program() {
+    match debug("aaa", "step\n").0 {
+        Err(InterpreterError::InputError { at, reason }) => {
+            assert_eq!((at.instruction, at.index), ('a', 1));
+            assert_eq!(reason, "cannot read debugger command: end of input");
+        },
+        other => panic!("expected an input error, got {:?}", other),
+    }
+}
+
+#[test]
+fn a_conditional_breakpoint_stops_where_the_condition_holds() {
+    let (_, lines) = debug("aaaaa", "brk if depth > 2\ncont\npidx\ncont\npidx\nquit\n");
+    assert_eq!(lines, vec![
+        "Breakpoint 1 set anywhere if depth > 2",
+        "Breakpoint 1 hit at 3 (1 time(s))",
+        "Program index: 3",
+        "Breakpoint 1 hit at 4 (2 time(s))",
+        "Program index: 4",
+    ]);
+
+    let (_, lines) = debug(LOOP, "brk 5 if top == 1\ncont\npidx\npstack\nquit\n");
+    assert_eq!(lines[1..], [
+        "Breakpoint 1 hit at 5 (1 time(s))",
+        "Program index: 5",
+        "Stack: Stack { stack: [1], max_size: Some(100) }",
+    ]);
+}
+
+#[test]
+fn watch_top_stops_once_the_value_changed() {
+    // The `q`s and the `b` leave a 0 on top, the `i` changes it
+    let (result, lines) = debug("aqqbib", "step\nwatch top\ncont\npidx\ncont\n");
+    assert_eq!(result, Ok(()));
+    assert_eq!(lines, vec![
+        "Watchpoint 1 set on top",
+        "Watchpoint 1: top changed from 0 to 1",
+        "Program index: 5",
+        "Watchpoint 1: top changed from 1 to 0",
+    ]);
+}
+
+#[test]
+fn watch_stack_slot_stops_on_a_write_of_the_same_value() {
+    // `l` swaps two 0s
+    let (_, lines) = debug("aal x", "watch stack[0]\ncont\npidx\ncont\npidx\nquit\n");
+    assert_eq!(lines, vec![
+        "Watchpoint 1 set on stack[0]",
+        "Watchpoint 1: stack[0] changed from none to 0",
+        "Program index: 1",
+        "Watchpoint 1: stack[0] written, still 0",
+        "Program index: 3",
+    ]);
+
+    // `i` saturating at the top of the range
+    let config = Config { domain: Domain { overflow: Overflow::Saturate, ..Domain::default() }, ..Config::default() };
+    let program = format!("a{}i x", "v".repeat(200));
+    let (_, lines) = debug_with(&program, "brk 201\ncont\nwatch stack[0]\ncont\npidx\nquit\n", config);
+    assert_eq!(lines[2..], [
+        "Watchpoint 2 set on stack[0]",
+        "Watchpoint 2: stack[0] written, still 1000",
+        "Program index: 202",
+    ]);
+}
+
+#[test]
+fn watch_rejects_constants_and_bad_operands() {
+    let (_, lines) = debug("aaa", "watch 3\nwatch stack[x]\nwatch bottom\nquit\n");
+    assert_eq!(lines, vec![
+        "Error: a constant never changes, watch top, depth or stack[<n>]",
+        "Error: invalid stack slot `x`",
+        "Error: unknown operand `bottom`, expected top, depth, stack[<n>] or an integer",
+    ]);
+}
+
+// The 2nd `b` fails on an empty stack.
+const UNDERFLOW: &str = "aibb x";
+
+const STOPPED: &str = "The program stopped on this error: `step` and `cont` retry the instruction, `quit` ends the program";
+
+fn underflow() -> InterpreterError {
+    let at = Position { instruction: 'b', index: 3, depth: 0 };
+    InterpreterError::StackUnderflow { at, needed: 1 }
+}
+
+#[test]
+fn break_on_error_prompts_at_the_failing_instruction() {
+    let (result, lines) = break_on_error(UNDERFLOW, "cont\npidx\npstack\nquit\n");
+    assert_eq!(result, Err(underflow()));
+    assert_eq!(lines, vec![
+        format!("Error: {}", underflow()),
+        String::from(STOPPED),
+        String::from("Program index: 3"),
+        String::from("Stack: Stack { stack: [], max_size: Some(100) }"),
+    ]);
+}
+
+#[test]
+fn step_retries_the_failing_instruction() {
+    // It fails again on the same stack
+    let (result, lines) = break_on_error(UNDERFLOW, "cont\nstep\npidx\nquit\n");
+    assert_eq!(result, Err(underflow()));
+    assert_eq!(lines[2..], [format!("Error: {}", underflow()), String::from(STOPPED), String::from("Program index: 3")]);
+
+    // Once the stack is fixed, it succeeds and the program no longer has an error
+    let (result, lines) = break_on_error(UNDERFLOW, "cont\nexec a\nstep\npidx\nquit\n");
+    assert_eq!(result, Ok(()));
+    assert_eq!(lines[2..], ["Program index: 4"]);
+}
+
+#[test]
+fn quit_after_an_exec_still_returns_the_error() {
+    let (result, _) = break_on_error(UNDERFLOW, "cont\nexec a\nquit\n");
+    assert_eq!(result, Err(underflow()));
+}
+
+#[test]
+fn without_break_on_error_the_program_ends_on_the_error() {
+    let (result, lines) = debug(UNDERFLOW, "cont\n");
+    assert_eq!(result, Err(underflow()));
+    assert!(lines.is_empty());
+}
@@ -0,0 +1,103 @@
+// Errors rendered against the source, rustc style: the file, line and column, the
+// source line and a caret under the character, even after tabs. Parsing reports every
+// invalid character this way, not only the first one.
+
+use ssl::parser;
+use ssl::parser::ParseError;
+use ssl::Diagnostic;
+use ssl::SourceMap;
+
+// Instructions on 4 lines, the 3rd one indented with a tab and the 4th with spaces.
+const SOURCE: &str = "aa // two zeros\n\n\tbq // tab\n   x  b";
+
+#[test]
+fn runtime_errors_point_at_the_instruction() {
+    let program = parser::parse_program("prog.ssl", SOURCE).unwrap();
+    assert_eq!(program.code, "aabqxb");
+    let diagnostic = Diagnostic::at_index("`x` failed", &program.source_map, 4);
+    assert_eq!(diagnostic.to_string(), "\
+error: `x` failed
+ --> prog.ssl:4:4
+  |
+4 |    x  b
+  |    ^");
+    let diagnostic = Diagnostic::at_index("`b` failed", &program.source_map, 5);
+    assert_eq!(diagnostic.to_string(), "\
+error: `b` failed
+ --> prog.ssl:4:7
+  |
+4 |    x  b
+  |       ^");
+}
+
+#[test]
+fn the_caret_keeps_the_tabs_of_the_line() {
+    let program = parser::parse_program("prog.ssl", SOURCE).unwrap();
+    let diagnostic = Diagnostic::at_index("`q` failed", &program.source_map, 3);
+    assert_eq!(diagnostic.to_string(), "\
+error: `q` failed
+ --> prog.ssl:3:3
+  |
+3 | \tbq // tab
+  | \t ^");
+}
+
+#[test]
+fn the_gutter_fits_the_line_number() {
+    let source = format!("{}ab", "\n".repeat(11));
+    let program = parser::parse_program("long.ssl", &source).unwrap();
+    let diagnostic = Diagnostic::at_index("`b` failed", &program.source_map, 1);
+    assert_eq!(diagnostic.to_string(), "\
+error: `b` failed
+  --> long.ssl:12:2
+   |
+12 | ab
+   |  ^");
+}
+
+#[test]
+fn an_index_past_the_program_only_names_the_file() {
+    let source_map = SourceMap::new("prog.ssl", SOURCE);
+    assert_eq!(Diagnostic::at_index("the domain is empty", &source_map, usize::MAX).to_string(), "\
+error: the domain is empty
+ --> prog.ssl");
+}
+
+#[test]
+fn every_invalid_character_is_reported() {
+    let source = "aa // fine: 1?\n\tab1 // tab\na\n  b?  c";
+    let characters = match parser::parse_program("prog.ssl", source) {
+        Err(ParseError::InvalidCharacters(x)) => x,
+        other => panic!("expected invalid characters, got {:?}", other),
+    };
+    let found: Vec<(char, usize, usize)> = characters.iter()
+        .map(|invalid| (invalid.character, invalid.location.line, invalid.location.column))
+        .collect();
+    assert_eq!(found, vec![('1', 2, 4), ('?', 4, 4)]);
+    let err = parser::parse_program("prog.ssl", source).unwrap_err();
+    assert_eq!(err.to_string(), "\
+error: invalid character `1`
+ --> prog.ssl:2:4
+  |
+2 | \tab1 // tab
+  | \t  ^
+
+error: invalid character `?`
+ --> prog.ssl:4:4
+  |
+4 |   b?  c
+  |    ^
+
+error: aborting due to 2 invalid character(s)");
+}
+
+#[test]
+fn invalid_characters_of_an_expression_are_all_reported() {
+    match parser::parse_expression("<command line>", "a1b 2") {
+        Err(ParseError::InvalidCharacters(characters)) => {
+            let columns: Vec<usize> = characters.iter().map(|invalid| invalid.location.column).collect();
+            assert_eq!(columns, vec![2, 5]);
+        },
+        other => panic!("expected invalid characters, got {:?}", other),
+    }
+}
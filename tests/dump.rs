@@ -0,0 +1,58 @@
+// Post-mortem dumps: the state `Interpreter::dump` describes and the file `--dump`
+// writes when a program fails.
+
+use std::env;
+use std::fs;
+use std::process::Command;
+use ssl::BufferIo;
+use ssl::Config;
+use ssl::Interpreter;
+
+fn dump_after_run(program: &str, config: Config) -> String {
+    let mut interpreter = Interpreter::with_io(program, config, BufferIo::new("")).unwrap();
+    let _ = interpreter.run();
+    interpreter.dump()
+}
+
+#[test]
+fn the_dump_shows_the_failing_instruction_and_the_stack() {
+    assert_eq!(dump_after_run("avaivo x", Config::default()), "\
+program index: 5
+instruction: `o`
+stack depth: 2 (max 100)
+stack (bottom first): [5, 6]
+");
+}
+
+#[test]
+fn the_dump_of_an_ended_program_has_no_instruction() {
+    let config = Config { max_stack_size: None, ..Config::default() };
+    assert_eq!(dump_after_run("aaix", config), "\
+program index: 4
+instruction: none, the program ended
+stack depth: 2 (max unbounded)
+stack (bottom first): [0, 1]
+");
+}
+
+#[test]
+fn dump_writes_the_error_and_its_location_first() {
+    let path = env::temp_dir().join(format!("ssl-dump-test-{}.txt", std::process::id()));
+    let status = Command::new(env!("CARGO_BIN_EXE_ssl"))
+        .args(["run", "avaivo x", "--dump"])
+        .arg(&path)
+        .output()
+        .unwrap()
+        .status;
+    assert_eq!(status.code(), Some(1));
+    let dump = fs::read_to_string(&path).unwrap();
+    let _ = fs::remove_file(&path);
+    assert_eq!(dump, "\
+error: `o` at index 5: stack index 6 is out of range, the stack has 2 item(s)
+location: <command line>:1:6
+program index: 5
+instruction: `o`
+stack depth: 2 (max 100)
+stack (bottom first): [5, 6]
+");
+}
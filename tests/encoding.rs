@@ -0,0 +1,133 @@
+// Characters printed by `f` and read by `j` in the byte, latin1 and utf8 encodings,
+// including the values and the input that aren't characters of the encoding.
+
+mod common;
+
+use ssl::Config;
+use ssl::Domain;
+use ssl::Encoding;
+use ssl::Eof;
+use ssl::InterpreterError;
+use crate::common::at;
+use crate::common::input_error;
+use crate::common::run_with;
+
+const INVALID_UTF8: &str = "input is not valid utf8 text";
+
+// Large enough for every Unicode code point.
+fn with_encoding(encoding: Encoding) -> Config {
+    Config {
+        encoding,
+        domain: Domain { min: -1000, max: 0x10ffff + 1, ..Domain::default() },
+        ..Config::default()
+    }
+}
+
+// What `f` prints for `value`, read by `h`.
+fn print(value: i64, encoding: Encoding) -> Result<Vec<u8>, InterpreterError> {
+    let (result, _, output) = run_with("hf", format!("{}\n", value).as_bytes(), with_encoding(encoding));
+    result.map(|_| output)
+}
+
+fn not_a_character(value: i64, encoding: Encoding) -> InterpreterError {
+    InterpreterError::NotACharacter { at: at('f', 1, 1), value, encoding }
+}
+
+#[test]
+fn byte_prints_raw_bytes() {
+    assert_eq!(print(65, Encoding::Byte), Ok(vec![65]));
+    assert_eq!(print(0, Encoding::Byte), Ok(vec![0]));
+    assert_eq!(print(200, Encoding::Byte), Ok(vec![200]));
+    assert_eq!(print(255, Encoding::Byte), Ok(vec![255]));
+}
+
+#[test]
+fn latin1_prints_utf8() {
+    assert_eq!(print(65, Encoding::Latin1), Ok(b"A".to_vec()));
+    assert_eq!(print(200, Encoding::Latin1), Ok("È".as_bytes().to_vec()));
+    assert_eq!(print(255, Encoding::Latin1), Ok("ÿ".as_bytes().to_vec()));
+}
+
+#[test]
+fn byte_and_latin1_fail_above_255() {
+    for encoding in [Encoding::Byte, Encoding::Latin1].iter() {
+        assert_eq!(print(256, *encoding), Err(not_a_character(256, *encoding)));
+        assert_eq!(print(1000, *encoding), Err(not_a_character(1000, *encoding)));
+        assert_eq!(print(-1, *encoding), Err(not_a_character(-1, *encoding)));
+    }
+}
+
+#[test]
+fn utf8_prints_any_code_point() {
+    assert_eq!(print(65, Encoding::Utf8), Ok(b"A".to_vec()));
+    assert_eq!(print(256, Encoding::Utf8), Ok("Ā".as_bytes().to_vec()));
+    assert_eq!(print(0x20ac, Encoding::Utf8), Ok("€".as_bytes().to_vec()));
+    assert_eq!(print(0x1f600, Encoding::Utf8), Ok("😀".as_bytes().to_vec()));
+    assert_eq!(print(0x10ffff, Encoding::Utf8), Ok("\u{10ffff}".as_bytes().to_vec()));
+}
+
+#[test]
+fn utf8_fails_on_values_that_are_not_code_points() {
+    for value in [-1, 0xd800, 0xdfff, 0x10ffff + 1].iter() {
+        assert_eq!(print(*value, Encoding::Utf8), Err(not_a_character(*value, Encoding::Utf8)));
+    }
+}
+
+#[test]
+fn the_default_encoding_is_latin1() {
+    assert_eq!(Config::default().encoding, Encoding::Latin1);
+}
+
+#[test]
+fn byte_and_latin1_read_one_byte() {
+    for encoding in [Encoding::Byte, Encoding::Latin1].iter() {
+        let (result, stack, _) = run_with("jjj", "é\u{ff}".as_bytes(), with_encoding(*encoding));
+        assert_eq!(result, Ok(()));
+        assert_eq!(stack, vec![0xc3, 0xa9, 0xc3], "{}", encoding);
+        assert_eq!(run_with("j", [0xff], with_encoding(*encoding)).1, vec![0xff]);
+    }
+}
+
+#[test]
+fn utf8_reads_a_whole_code_point() {
+    let (result, stack, _) = run_with("jjjj", "aé€😀".as_bytes(), with_encoding(Encoding::Utf8));
+    assert_eq!(result, Ok(()));
+    assert_eq!(stack, vec![0x61, 0xe9, 0x20ac, 0x1f600]);
+}
+
+#[test]
+fn utf8_round_trips() {
+    let text = "aé€😀\n";
+    let (result, _, output) = run_with("jtfbju", text.as_bytes(), Config { eof: Eof::Zero, ..with_encoding(Encoding::Utf8) });
+    assert_eq!(result, Ok(()));
+    assert_eq!(output, text.as_bytes());
+}
+
+#[test]
+fn utf8_rejects_invalid_input() {
+    let config = with_encoding(Encoding::Utf8);
+    // A continuation byte, a byte that never starts a character and an overlong encoding
+    for input in [&[0x80][..], &[0xff], &[0xc0, 0x80], &[0xe0, 0x80, 0x80]].iter() {
+        assert_eq!(run_with("j", input, config.clone()).0, Err(input_error('j', 0, 0, INVALID_UTF8)), "{:?}", input);
+    }
+    // A surrogate and a value above the last code point
+    assert_eq!(run_with("j", [0xed, 0xa0, 0x80], config.clone()).0, Err(input_error('j', 0, 0, INVALID_UTF8)));
+    assert_eq!(run_with("j", [0xf4, 0x90, 0x80, 0x80], config.clone()).0, Err(input_error('j', 0, 0, INVALID_UTF8)));
+    // A character whose 2nd byte isn't a continuation byte
+    assert_eq!(run_with("aj", [0xc3, 0x41], config).0, Err(input_error('j', 1, 1, INVALID_UTF8)));
+}
+
+#[test]
+fn utf8_rejects_truncated_input() {
+    let config = with_encoding(Encoding::Utf8);
+    for input in [&[0xc3][..], &[0xe2, 0x82], &[0xf0, 0x9f, 0x98]].iter() {
+        assert_eq!(run_with("j", input, config.clone()).0, Err(input_error('j', 0, 0, INVALID_UTF8)), "{:?}", input);
+    }
+}
+
+#[test]
+fn read_characters_must_be_in_the_domain() {
+    let config = Config { encoding: Encoding::Utf8, ..Config::default() };
+    let expected = InterpreterError::ValueOutOfRange { at: at('j', 0, 0), value: 0x20ac, min: 0, max: 1000 };
+    assert_eq!(run_with("j", "€".as_bytes(), config).0, Err(expected));
+}
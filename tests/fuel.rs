@@ -0,0 +1,180 @@
+// Fuel accounting of `run`: a run with `n` units of fuel must stop exactly where `n`
+// calls to `step` stop, whatever the ops the bytecode merged, and a run resumed in
+// chunks must end like a run without limit.
+
+use ssl::parser;
+use ssl::BufferIo;
+use ssl::Config;
+use ssl::Domain;
+use ssl::Interpreter;
+use ssl::Outcome;
+use ssl::Overflow;
+use ssl::Profile;
+
+// The stack, bottom first, the program index, whether the program ended, what it
+// printed and the error it stopped with.
+type State = (Vec<i64>, usize, bool, String, Option<String>);
+
+fn state(interpreter: &Interpreter<BufferIo>, error: Option<String>) -> State {
+    (
+        interpreter.memory().items().to_vec(),
+        interpreter.program_index(),
+        interpreter.is_finished(),
+        interpreter.io().output_string(),
+        error,
+    )
+}
+
+fn programs() -> Vec<(String, Config)> {
+    let hello_world = parser::parse_program("hello_world", include_str!("../src/part_3_hello_world.txt")).unwrap();
+    let wrap = Domain { min: -10, max: 10, overflow: Overflow::Wrap };
+    let programs = vec![
+        // Constants and merged runs of `i`/`v` and `d`/`w`
+        ("avvvii avvvvwwww g x", Config::default()),
+        // A `tdu` loop, one op costing 1 + 2 × the top item
+        ("avvtdu x", Config::default()),
+        // A negative top item, the `tdu` loop runs one instruction at a time
+        ("addd tdu x", Config { domain: wrap, ..Config::default() }),
+        // Loops, `qb` and skips
+        ("aiiiit dx u", Config::default()),
+        ("avtqbdu x", Config::default()),
+        ("akiaikix", Config::default()),
+        // Failures inside a merged run and after a `qb`
+        ("aiiddd x", Config::default()),
+        ("aqbbb", Config::default()),
+        // Instructions ignored by the profile are free
+        ("avvc ii x", Config { profile: Profile::StackBasic, ..Config::default() }),
+        ("aiitdxu", Config { profile: Profile::StackBasic, ..Config::default() }),
+    ];
+    let mut programs: Vec<(String, Config)> = programs.into_iter()
+        .map(|(program, config)| (program.to_string(), config))
+        .collect();
+    programs.push((hello_world.code, Config::default()));
+    programs
+}
+
+// Steps until `fuel` instructions of the profile were executed, then over the ignored
+// ones before the next instruction, as a run that ran out of fuel does.
+fn step_with_fuel(program: &str, config: Config, fuel: u64) -> State {
+    let profile = config.profile;
+    let mut interpreter = Interpreter::with_io(program, config, BufferIo::new("")).unwrap();
+    let mut executed = 0;
+    while let Some(instruction) = interpreter.current_instruction() {
+        if profile.contains(instruction) {
+            if executed == fuel {
+                break;
+            }
+            executed += 1;
+        }
+        if let Err(err) = interpreter.step() {
+            return state(&interpreter, Some(err.to_string()));
+        }
+    }
+    state(&interpreter, None)
+}
+
+// Number of instructions of the profile a run without limit executes.
+fn instruction_count(program: &str, config: Config) -> u64 {
+    let profile = config.profile;
+    let mut interpreter = Interpreter::with_io(program, config, BufferIo::new("")).unwrap();
+    let mut executed = 0;
+    while let Some(instruction) = interpreter.current_instruction() {
+        if profile.contains(instruction) {
+            executed += 1;
+        }
+        if interpreter.step().is_err() {
+            break;
+        }
+    }
+    executed
+}
+
+fn run_to_end(program: &str, config: Config) -> State {
+    let mut interpreter = Interpreter::with_io(program, config, BufferIo::new("")).unwrap();
+    let error = interpreter.run().err().map(|err| err.to_string());
+    state(&interpreter, error)
+}
+
+#[test]
+fn running_with_n_units_of_fuel_stops_where_n_steps_do() {
+    for (program, config) in programs() {
+        let total = instruction_count(&program, config.clone());
+        for optimize in [true, false].iter() {
+            for fuel in 0..=total + 1 {
+                let config = Config { optimize: *optimize, fuel: Some(fuel), ..config.clone() };
+                let mut interpreter = Interpreter::with_io(&program, config.clone(), BufferIo::new("")).unwrap();
+                let result = interpreter.run();
+                let error = result.as_ref().err().map(|err| err.to_string());
+                let expected = step_with_fuel(&program, config, fuel);
+                assert_eq!(state(&interpreter, error), expected, "{} with {} unit(s) of fuel", program, fuel);
+                match result {
+                    Ok(Outcome::OutOfFuel) => {
+                        assert!(fuel < total, "{} ran out of fuel with {} unit(s)", program, fuel);
+                        assert_eq!(interpreter.fuel(), Some(0), "{} with {} unit(s) of fuel", program, fuel);
+                    },
+                    Ok(Outcome::Finished) => {
+                        assert_eq!(interpreter.fuel(), Some(fuel - total), "{} with {} unit(s) of fuel", program, fuel);
+                    },
+                    Err(_) => {},
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn resuming_in_chunks_ends_like_an_unlimited_run() {
+    for (program, config) in programs() {
+        let total = instruction_count(&program, config.clone());
+        for optimize in [true, false].iter() {
+            let unlimited = run_to_end(&program, Config { optimize: *optimize, ..config.clone() });
+            for chunk in [1, 2, 3, 5, 8, 13, 100].iter() {
+                let config = Config { optimize: *optimize, fuel: Some(*chunk), ..config.clone() };
+                let mut interpreter = Interpreter::with_io(&program, config, BufferIo::new("")).unwrap();
+                let mut given = *chunk;
+                let error = loop {
+                    match interpreter.run() {
+                        Ok(Outcome::OutOfFuel) => {
+                            interpreter.add_fuel(*chunk);
+                            given += *chunk;
+                        },
+                        Ok(Outcome::Finished) => break None,
+                        Err(err) => break Some(err.to_string()),
+                    }
+                };
+                assert_eq!(state(&interpreter, error.clone()), unlimited, "{} in chunks of {}", program, chunk);
+                if error.is_none() {
+                    assert_eq!(given - interpreter.fuel().unwrap(), total, "{} in chunks of {}: fuel spent", program, chunk);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn a_run_without_fuel_executes_nothing() {
+    let config = Config { fuel: Some(0), ..Config::default() };
+    let mut interpreter = Interpreter::with_io("aix", config, BufferIo::new("")).unwrap();
+    assert_eq!(interpreter.run().unwrap(), Outcome::OutOfFuel);
+    assert_eq!(interpreter.program_index(), 0);
+    assert!(interpreter.memory().items().is_empty());
+}
+
+// `step` may stop in the middle of a merged op, `run` must finish it one instruction
+// at a time before going back to the bytecode.
+#[test]
+fn run_resumes_in_the_middle_of_a_merged_op() {
+    for optimize in [true, false].iter() {
+        let config = Config { optimize: *optimize, fuel: Some(3), ..Config::default() };
+        let mut interpreter = Interpreter::with_io("avvvvii x", config, BufferIo::new("")).unwrap();
+        interpreter.step().unwrap();
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.run().unwrap(), Outcome::OutOfFuel);
+        assert_eq!(interpreter.program_index(), 5);
+        assert_eq!(interpreter.memory().items(), &[20]);
+        interpreter.add_fuel(10);
+        assert_eq!(interpreter.run().unwrap(), Outcome::Finished);
+        assert_eq!(interpreter.io().output_string(), "22");
+        assert_eq!(interpreter.fuel(), Some(7));
+    }
+}
@@ -0,0 +1,160 @@
+// Reverse execution: undoing recorded instructions must restore exactly the state the
+// program had before them, whatever they did to the stack, the program index or the input.
+
+use ssl::BufferIo;
+use ssl::Config;
+use ssl::Eof;
+use ssl::Interpreter;
+use ssl::NumberInput;
+use ssl::Outcome;
+use ssl::Profile;
+
+// The stack, bottom first, the program index and whether the program ended.
+type State = (Vec<i64>, usize, bool);
+
+fn state(interpreter: &Interpreter<BufferIo>) -> State {
+    (interpreter.memory().items().to_vec(), interpreter.program_index(), interpreter.is_finished())
+}
+
+// Steps through the whole program, recording the state before each instruction.
+fn forward(interpreter: &mut Interpreter<BufferIo>) -> Vec<State> {
+    let mut states = vec![state(interpreter)];
+    while !interpreter.is_finished() {
+        interpreter.step().unwrap();
+        states.push(state(interpreter));
+    }
+    states
+}
+
+fn recording(program: &str, input: &str, config: Config) -> Interpreter<BufferIo> {
+    let mut interpreter = Interpreter::with_io(program, config, BufferIo::new(input)).unwrap();
+    interpreter.set_recording(true);
+    interpreter
+}
+
+// Runs `program` forward, undoes it back to the start, checking every state on the way,
+// then runs it forward again: it must go through the same states and print the same output.
+fn check_round_trip(program: &str, input: &str, config: Config) {
+    let mut interpreter = recording(program, input, config);
+    let states = forward(&mut interpreter);
+    assert_eq!(interpreter.history_len(), states.len() - 1, "{}", program);
+    for expected in states.iter().rev().skip(1) {
+        assert!(interpreter.undo(), "{}", program);
+        assert_eq!(&state(&interpreter), expected, "{}", program);
+    }
+    assert!(!interpreter.undo(), "{}", program);
+    assert_eq!(forward(&mut interpreter), states, "{}: replay differs", program);
+    let output = interpreter.io().output_string();
+    let half = output.len() / 2;
+    assert_eq!(output[..half], output[half..], "{}: replay printed something else", program);
+}
+
+#[test]
+fn undo_restores_the_stack() {
+    let programs = ["avqgx", "av avi ai o x", "av avi aii s xbxbx", "aaaay r x", "avaiic x", "avalb", "aviiav m pnx"];
+    for program in programs.iter() {
+        check_round_trip(program, "", Config::default());
+    }
+    // The cell profile overwrites its only cell with `a`
+    let cell = Config { profile: Profile::Cell, ..Config::default() };
+    check_round_trip("aiiixaix dy aix", "", cell);
+}
+
+// Undoing doesn't copy the stack, each kind of change is reverted on its own.
+#[test]
+fn undo_reverts_each_kind_of_change() {
+    // Deep `o` and `s`, swaps of equal items and a `y` on a full stack
+    let programs = ["avavavaiiio xbxbx", "aviavavaiiis xbxbxbx", "avqlqaiiisx", "aiqqqqy r x", "aiqqqbbbd x"];
+    for program in programs.iter() {
+        check_round_trip(program, "", Config::default());
+    }
+    // A `h` that reads nothing pushes nothing
+    let unchanged = Config { eof: Eof::Unchanged, ..Config::default() };
+    check_round_trip("hhhrx", "4\n", unchanged);
+}
+
+#[test]
+fn undo_restores_skips_and_jumps() {
+    let programs = ["akiaikix", "aiiitdux", "aiitaiitdubdux", "avtdx u", "aiiiitdkdu x", "axzax"];
+    for program in programs.iter() {
+        check_round_trip(program, "", Config::default());
+    }
+}
+
+#[test]
+fn undo_gives_the_input_back() {
+    check_round_trip("hjjhgx", "12\nA\n30\n", Config::default());
+    let stream = Config { number_input: NumberInput::Stream, ..Config::default() };
+    check_round_trip("hhhggx", "1 2\n 3\n", stream);
+}
+
+#[test]
+fn undone_input_is_read_before_the_rest() {
+    let mut interpreter = recording("hhh", "1\n2\n3\n", Config::default());
+    interpreter.step().unwrap();
+    interpreter.step().unwrap();
+    assert!(interpreter.undo());
+    interpreter.step().unwrap();
+    interpreter.step().unwrap();
+    assert_eq!(interpreter.memory().items(), &[1, 2, 3]);
+}
+
+#[test]
+fn undo_reverts_executed_instructions() {
+    let mut interpreter = recording("ax", "", Config::default());
+    interpreter.step().unwrap();
+    interpreter.execute('v').unwrap();
+    interpreter.execute('k').unwrap();
+    assert_eq!(state(&interpreter), (vec![5], 1, false));
+    assert!(interpreter.undo());
+    assert_eq!(state(&interpreter), (vec![5], 1, false));
+    assert!(interpreter.undo());
+    assert_eq!(state(&interpreter), (vec![0], 1, false));
+}
+
+#[test]
+fn run_is_recorded_while_recording() {
+    let mut interpreter = recording("aiq", "", Config::default());
+    interpreter.step().unwrap();
+    interpreter.run().unwrap();
+    assert_eq!(interpreter.history_len(), 3);
+    assert!(interpreter.undo());
+    assert_eq!(state(&interpreter), (vec![1], 2, false));
+    assert!(interpreter.undo());
+    assert_eq!(state(&interpreter), (vec![0], 1, false));
+    assert!(interpreter.undo());
+    assert_eq!(state(&interpreter), (vec![], 0, false));
+}
+
+#[test]
+fn run_out_of_fuel_or_failing_is_recorded_up_to_where_it_stopped() {
+    let mut interpreter = recording("aivvvb bb", "", Config { fuel: Some(4), ..Config::default() });
+    assert_eq!(interpreter.run(), Ok(Outcome::OutOfFuel));
+    assert_eq!(interpreter.history_len(), 4);
+    interpreter.set_fuel(None);
+    assert!(interpreter.run().is_err());
+    // The ignored space is recorded too, the failed `b` isn't
+    assert_eq!(state(&interpreter), (vec![], 7, false));
+    assert_eq!(interpreter.history_len(), 7);
+    assert!(interpreter.undo());
+    assert!(interpreter.undo());
+    assert_eq!(state(&interpreter), (vec![16], 5, false));
+}
+
+#[test]
+fn failed_instructions_are_not_recorded() {
+    let mut interpreter = recording("aib", "", Config::default());
+    interpreter.step().unwrap();
+    assert!(interpreter.execute('c').is_err());
+    assert_eq!(interpreter.history_len(), 1);
+    assert!(interpreter.undo());
+    assert_eq!(state(&interpreter), (vec![], 0, false));
+}
+
+#[test]
+fn nothing_is_recorded_by_default() {
+    let mut interpreter = Interpreter::with_io("aaa", Config::default(), BufferIo::new("")).unwrap();
+    interpreter.step().unwrap();
+    assert_eq!(interpreter.history_len(), 0);
+    assert!(!interpreter.undo());
+}
@@ -0,0 +1,99 @@
+// Reading input with `h` and `j`: the end of input behaviors of `--eof` and the
+// line and stream modes of `--numbers`.
+
+mod common;
+
+use ssl::Config;
+use ssl::Domain;
+use ssl::Eof;
+use ssl::Interpreter;
+use ssl::LoadError;
+use ssl::NumberInput;
+use ssl::Overflow;
+use crate::common::input_error;
+use crate::common::run_with;
+
+fn with_eof(eof: Eof) -> Config {
+    Config { eof, ..Config::default() }
+}
+
+fn stream() -> Config {
+    Config { number_input: NumberInput::Stream, ..Config::default() }
+}
+
+#[test]
+fn reading_past_the_end_fails_by_default() {
+    assert_eq!(run_with("hh", "5\n", Config::default()).0, Err(input_error('h', 1, 1, "cannot read a number, the input ended")));
+    assert_eq!(run_with("jj", "A", Config::default()).0, Err(input_error('j', 1, 1, "cannot read a char, the input ended")));
+}
+
+#[test]
+fn eof_zero_pushes_0() {
+    assert_eq!(run_with("hh", "5\n", with_eof(Eof::Zero)), (Ok(()), vec![5, 0], vec![]));
+    assert_eq!(run_with("jj", "A", with_eof(Eof::Zero)), (Ok(()), vec![65, 0], vec![]));
+}
+
+#[test]
+fn eof_sentinel_pushes_its_value() {
+    assert_eq!(run_with("hhh", "5\n", with_eof(Eof::Sentinel(7))).1, vec![5, 7, 7]);
+    assert_eq!(run_with("jj", "", with_eof(Eof::Sentinel(1000))).1, vec![1000, 1000]);
+}
+
+#[test]
+fn eof_sentinel_must_be_in_the_domain() {
+    let expected = LoadError::SentinelOutOfRange { value: -1, min: 0, max: 1000 };
+    assert_eq!(Interpreter::new("h", with_eof(Eof::Sentinel(-1))).err(), Some(expected));
+    let wrap = Domain { overflow: Overflow::Wrap, ..Domain::default() };
+    let expected = LoadError::SentinelOutOfRange { value: 1001, min: 0, max: 1000 };
+    assert_eq!(Interpreter::new("h", Config { domain: wrap, ..with_eof(Eof::Sentinel(1001)) }).err(), Some(expected));
+    let signed = Config { domain: Domain { min: -1, max: 1000, ..Domain::default() }, ..with_eof(Eof::Sentinel(-1)) };
+    assert_eq!(run_with("h", "", signed).1, vec![-1]);
+}
+
+#[test]
+fn eof_unchanged_pushes_nothing() {
+    assert_eq!(run_with("hh", "5\n", with_eof(Eof::Unchanged)), (Ok(()), vec![5], vec![]));
+    assert_eq!(run_with("jjj", "A", with_eof(Eof::Unchanged)), (Ok(()), vec![65], vec![]));
+}
+
+#[test]
+fn a_program_can_loop_over_its_whole_input() {
+    assert_eq!(run_with("jtfbju", "hello", with_eof(Eof::Zero)).2, b"hello");
+}
+
+#[test]
+fn lines_hold_one_number() {
+    assert_eq!(run_with("hhh", "1\n 2 \n3", Config::default()).1, vec![1, 2, 3]);
+    let expected = input_error('h', 0, 0, "input `1 2` is not an integer");
+    assert_eq!(run_with("hh", "1 2\n", Config::default()).0, Err(expected));
+}
+
+#[test]
+fn an_empty_line_is_not_the_end_of_input() {
+    let expected = input_error('h', 0, 0, "input `` is not an integer");
+    assert_eq!(run_with("h", "\n", with_eof(Eof::Zero)).0, Err(expected));
+}
+
+#[test]
+fn a_stream_holds_numbers_separated_by_any_whitespace() {
+    assert_eq!(run_with("hhhhh", "1 2\n\n 3\t4\r\n  5", stream()).1, vec![1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn a_stream_ends_when_only_whitespace_is_left() {
+    let config = Config { eof: Eof::Zero, ..stream() };
+    assert_eq!(run_with("hhh", "1 \n \t\n", config).1, vec![1, 0, 0]);
+}
+
+#[test]
+fn a_stream_rejects_a_word_that_is_not_a_number() {
+    let expected = input_error('h', 1, 1, "input `2a` is not an integer");
+    assert_eq!(run_with("hhh", "1 2a 3", stream()).0, Err(expected));
+}
+
+// The whitespace right after a number is consumed with it, `j` reads what follows.
+#[test]
+fn j_reads_after_the_number_read_by_h() {
+    assert_eq!(run_with("hj", "12 A", stream()).1, vec![12, 65]);
+    assert_eq!(run_with("hj", "12\nA", Config::default()).1, vec![12, 65]);
+}
@@ -0,0 +1,70 @@
+// Sandbox limits: `LimitedIo` cuts the output at its limit and makes `f` fail, and
+// `--timeout` only accepts durations it can wait for.
+
+use std::process::Command;
+use ssl::parser;
+use ssl::BufferIo;
+use ssl::Config;
+use ssl::Interpreter;
+use ssl::InterpreterError;
+use ssl::LimitedIo;
+
+fn hello_world() -> String {
+    parser::parse_program("hello_world", include_str!("../src/part_3_hello_world.txt")).unwrap().code
+}
+
+fn run_limited(program: &str, limit: u64) -> (Result<(), InterpreterError>, LimitedIo<BufferIo>) {
+    let io = LimitedIo::new(BufferIo::new(""), limit);
+    let mut interpreter = Interpreter::with_io(program, Config::default(), io).unwrap();
+    let result = interpreter.run().map(|_| ());
+    (result, interpreter.into_io())
+}
+
+#[test]
+fn output_is_cut_at_the_limit() {
+    let (result, io) = run_limited(&hello_world(), 5);
+    assert_eq!(io.inner().output(), b"Hello");
+    assert!(io.limit_reached());
+    match result {
+        Err(InterpreterError::OutputError { at, reason }) => {
+            assert_eq!(at.instruction, 'f');
+            assert_eq!(reason, "cannot print: output limit of 5 byte(s) reached");
+        },
+        other => panic!("expected an output error, got {:?}", other),
+    }
+}
+
+#[test]
+fn output_up_to_the_limit_is_allowed() {
+    let expected = {
+        let (result, io) = run_limited(&hello_world(), u64::MAX);
+        assert_eq!(result, Ok(()));
+        io.into_inner().output().to_vec()
+    };
+    let (result, io) = run_limited(&hello_world(), expected.len() as u64);
+    assert_eq!(result, Ok(()));
+    assert!(!io.limit_reached());
+    assert_eq!(io.inner().output(), &expected[..]);
+}
+
+#[test]
+fn a_limit_of_0_fails_on_the_first_character() {
+    let (result, io) = run_limited("aiiif", 0);
+    assert!(matches!(result, Err(InterpreterError::OutputError { .. })));
+    assert!(io.limit_reached());
+    assert!(io.inner().output().is_empty());
+}
+
+#[test]
+fn timeouts_too_long_to_wait_for_are_usage_errors() {
+    for timeout in ["1e300", "inf", "nan", "0", "-1"].iter() {
+        let output = Command::new(env!("CARGO_BIN_EXE_ssl"))
+            .args(["run", "ax", "--timeout", timeout])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2), "--timeout {}", timeout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let expected = format!("invalid value `{}` for `--timeout`, expected a positive number of seconds", timeout);
+        assert!(stderr.contains(&expected), "--timeout {}: {}", timeout, stderr);
+    }
+}
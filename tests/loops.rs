@@ -0,0 +1,88 @@
+// `t`/`u` loops: matching them when the program is loaded, rejecting the ones left
+// unmatched and, with `lenient_loops`, the jumps the original interpreter made for them.
+
+mod common;
+
+use ssl::BufferIo;
+use ssl::Config;
+use ssl::Interpreter;
+use ssl::LoadError;
+use ssl::Outcome;
+use ssl::SourceMap;
+use ssl::UnmatchedLoop;
+use crate::common::optimized_and_not;
+use crate::common::run_with;
+
+fn lenient() -> Config {
+    Config { lenient_loops: true, ..Config::default() }
+}
+
+fn unmatched(instruction: char, index: usize) -> UnmatchedLoop {
+    UnmatchedLoop { instruction, index }
+}
+
+#[test]
+fn nested_loops_are_matched() {
+    // The outer loop counts 3 down, the inner one empties a 2 on each iteration
+    let program = "aiiit aiitdu b dx u";
+    let output = optimized_and_not(program, &Config::default(), |config| run_with(program, "", config));
+    assert_eq!(output, (Ok(()), vec![0], b"210".to_vec()));
+}
+
+#[test]
+fn every_unmatched_loop_is_reported_in_index_order() {
+    let err = Interpreter::new("uatxu ut t", Config::default()).err().unwrap();
+    let expected = vec![unmatched('u', 0), unmatched('u', 6), unmatched('t', 7), unmatched('t', 9)];
+    assert_eq!(err, LoadError::UnmatchedLoops(expected));
+    assert_eq!(err.to_string(), "\
+unmatched `u` at index 0: no `t` opens this loop, \
+unmatched `u` at index 6: no `t` opens this loop, \
+unmatched `t` at index 7: no `u` closes this loop, \
+unmatched `t` at index 9: no `u` closes this loop");
+    assert_eq!(err.diagnostics(&SourceMap::new("<test>", "uatxu ut t")).len(), 4);
+}
+
+#[test]
+fn a_rejected_program_leaves_the_current_one_loaded() {
+    let mut interpreter = Interpreter::with_io("aix", Config::default(), BufferIo::new("")).unwrap();
+    assert!(interpreter.load_program("tt").is_err());
+    assert_eq!(interpreter.program(), &['a', 'i', 'x']);
+}
+
+#[test]
+fn a_lenient_lone_t_jumps_to_the_end() {
+    let program = "at x";
+    let (result, stack, output) = optimized_and_not(program, &lenient(), |config| run_with(program, "", config));
+    assert_eq!((result, stack, output), (Ok(()), vec![0], vec![]));
+
+    let mut interpreter = Interpreter::with_io(program, lenient(), BufferIo::new("")).unwrap();
+    interpreter.step().unwrap();
+    interpreter.step().unwrap();
+    assert_eq!(interpreter.program_index(), 4);
+    assert!(interpreter.is_finished());
+
+    // It doesn't jump when the top item isn't 0
+    assert_eq!(run_with("ait x", "", lenient()).2, b"1");
+}
+
+#[test]
+fn a_lenient_lone_u_jumps_to_index_1() {
+    let program = "ai x u";
+    let mut interpreter = Interpreter::with_io(program, lenient(), BufferIo::new("")).unwrap();
+    for _ in 0..6 {
+        interpreter.step().unwrap();
+    }
+    assert_eq!(interpreter.program_index(), 1);
+
+    // `a i x u` then `i x u` from index 1
+    for optimize in [true, false].iter() {
+        let config = Config { fuel: Some(7), optimize: *optimize, ..lenient() };
+        let mut interpreter = Interpreter::with_io(program, config, BufferIo::new("")).unwrap();
+        assert_eq!(interpreter.run(), Ok(Outcome::OutOfFuel));
+        assert_eq!(interpreter.program_index(), 1);
+        assert_eq!(interpreter.io().output_string(), "12");
+    }
+
+    // It doesn't jump when the top item is 0
+    assert_eq!(run_with("ax u x", "", lenient()).2, b"00");
+}
@@ -0,0 +1,66 @@
+// The bytecode the peephole optimizer produces: which idioms it rewrites and where
+// jump targets keep it from fusing ops. Whether the rewritten bytecode runs like the
+// original one is checked by the differential suites.
+
+use ssl::bytecode::Op;
+use ssl::bytecode::Span;
+use ssl::Config;
+use ssl::Interpreter;
+
+fn ops(program: &str) -> Vec<Op> {
+    Interpreter::new(program, Config::default()).unwrap().bytecode().ops.clone()
+}
+
+fn unoptimized_ops(program: &str) -> Vec<Op> {
+    let config = Config { optimize: false, ..Config::default() };
+    Interpreter::new(program, config).unwrap().bytecode().ops.clone()
+}
+
+#[test]
+fn a_constant_is_pushed_directly() {
+    let interpreter = Interpreter::new("avvvii", Config::default()).unwrap();
+    assert_eq!(interpreter.bytecode().ops, vec![Op::Push(17)]);
+    assert_eq!(interpreter.bytecode().spans, vec![Span { index: 0, len: 6 }]);
+    assert_eq!(unoptimized_ops("avvvii").len(), 2);
+}
+
+#[test]
+fn runs_of_v_w_i_d_become_one_addition() {
+    match ops("avvx vvwid")[..] {
+        [Op::Push(10), Op::PrintNumber, Op::AddConst { delta: 5, .. }] => {},
+        ref other => panic!("expected a single addition, got {:?}", other),
+    }
+}
+
+#[test]
+fn qb_becomes_a_guard() {
+    assert_eq!(ops("qb"), vec![Op::Guard { depth: 1, room: 1 }]);
+    assert_eq!(ops("aqbqb"), vec![Op::Push(0), Op::Guard { depth: 1, room: 1 }]);
+    assert_eq!(unoptimized_ops("qb"), vec![Op::Duplicate, Op::Pop]);
+}
+
+#[test]
+fn tdu_sets_the_top_item_to_0() {
+    assert_eq!(ops("avtdu x"), vec![Op::Push(5), Op::ZeroTop, Op::PrintNumber]);
+    assert_eq!(unoptimized_ops("tdu"), vec![Op::JumpIfZero(3), Op::Decrement, Op::JumpIfNotZero(1)]);
+}
+
+#[test]
+fn a_skip_target_is_not_fused_with_the_op_before_it() {
+    assert_eq!(ops("aii"), vec![Op::Push(2)]);
+    // The `k` can land on the 2nd `i`, which must stay an op of its own
+    assert_eq!(ops("akii"), vec![Op::Push(0), Op::SkipIfZero(3), Op::Increment, Op::Increment]);
+    // Landing on the `d` of a `tdu` keeps the loop
+    assert_eq!(ops("aiktdu x"), vec![
+        Op::Push(1), Op::SkipIfZero(3), Op::JumpIfZero(5), Op::Decrement, Op::JumpIfNotZero(3), Op::PrintNumber,
+    ]);
+}
+
+#[test]
+fn a_loop_target_starts_a_new_op() {
+    // The `u` jumps back to the 1st `i` of the body, the body is fused from there on
+    match ops("aviitiiu x")[..] {
+        [Op::Push(7), Op::JumpIfZero(4), Op::AddConst { delta: 2, .. }, Op::JumpIfNotZero(2), Op::PrintNumber] => {},
+        ref other => panic!("expected the loop body to be one op, got {:?}", other),
+    }
+}
@@ -0,0 +1,124 @@
+// Edge cases of `o` and `s`, which take the position of an item counting from
+// the top of the stack, the index cell itself being item 0.
+
+mod common;
+
+use ssl::BufferIo;
+use ssl::Config;
+use ssl::Domain;
+use ssl::Interpreter;
+use ssl::InterpreterError;
+use crate::common::at;
+use crate::common::index_error;
+use crate::common::step_to_end;
+
+// Runs `program` and returns the result along with the stack, bottom first.
+fn run_with(program: &str, config: Config) -> (Result<(), InterpreterError>, Vec<i64>) {
+    let (result, stack, _) = common::run_with(program, "", config);
+    (result, stack)
+}
+
+fn run(program: &str) -> (Result<(), InterpreterError>, Vec<i64>) {
+    run_with(program, Config::default())
+}
+
+// A domain with negative values, so that the index can be negative.
+fn signed() -> Config {
+    Config {
+        domain: Domain { min: -1000, max: 1000, ..Domain::default() },
+        ..Config::default()
+    }
+}
+
+#[test]
+fn s_with_index_0_swaps_the_index_cell_with_itself() {
+    assert_eq!(run("aviaa s"), (Ok(()), vec![6, 0, 0]));
+}
+
+#[test]
+fn s_swaps_the_index_cell_with_the_nth_item() {
+    // Stack: 5 6 2, the 2nd item from the top is 5
+    assert_eq!(run("av avi aii s"), (Ok(()), vec![2, 6, 5]));
+}
+
+#[test]
+fn s_with_the_deepest_index_swaps_with_the_bottom() {
+    assert_eq!(run("aaaaiii s"), (Ok(()), vec![3, 0, 0, 0]));
+}
+
+#[test]
+fn s_with_an_index_equal_to_the_depth_fails() {
+    assert_eq!(run("aaaiii s").0, Err(index_error('s', 7, 3, 3)));
+}
+
+#[test]
+fn s_with_a_large_index_fails() {
+    // `v` stops short of 1000 as in the original interpreter, `i` reaches it
+    let program = format!("a{}iiiii s", "v".repeat(199));
+    assert_eq!(run(&program).0, Err(index_error('s', 206, 1, 1000)));
+}
+
+#[test]
+fn s_with_a_negative_index_fails() {
+    assert_eq!(run_with("aad s", signed()).0, Err(index_error('s', 4, 2, -1)));
+}
+
+#[test]
+fn s_on_an_empty_stack_underflows() {
+    assert_eq!(run("s").0, Err(InterpreterError::StackUnderflow { at: at('s', 0, 0), needed: 1 }));
+}
+
+#[test]
+fn o_with_index_0_removes_its_own_index_cell() {
+    assert_eq!(run("avi a o"), (Ok(()), vec![6]));
+}
+
+#[test]
+fn o_removes_the_nth_item() {
+    // Stack: 5 6 1, the 1st item from the top is 6
+    assert_eq!(run("av avi ai o"), (Ok(()), vec![5, 1]));
+}
+
+#[test]
+fn o_with_the_deepest_index_removes_the_bottom() {
+    assert_eq!(run("avaaii o"), (Ok(()), vec![0, 2]));
+}
+
+#[test]
+fn o_with_only_the_index_cell_removes_it() {
+    assert_eq!(run("a o"), (Ok(()), vec![]));
+}
+
+#[test]
+fn o_with_an_index_equal_to_the_depth_fails() {
+    assert_eq!(run("aaii o").0, Err(index_error('o', 5, 2, 2)));
+}
+
+#[test]
+fn o_with_a_large_index_fails() {
+    // `v` stops short of 1000 as in the original interpreter, `i` reaches it
+    let program = format!("a{}iiiii o", "v".repeat(199));
+    assert_eq!(run(&program).0, Err(index_error('o', 206, 1, 1000)));
+}
+
+#[test]
+fn o_with_a_negative_index_fails() {
+    assert_eq!(run_with("ad o", signed()).0, Err(index_error('o', 3, 1, -1)));
+}
+
+#[test]
+fn o_on_an_empty_stack_underflows() {
+    assert_eq!(run("o").0, Err(InterpreterError::StackUnderflow { at: at('o', 0, 0), needed: 1 }));
+}
+
+#[test]
+fn failed_o_and_s_leave_the_stack_untouched() {
+    assert_eq!(run("avaiii o"), (Err(index_error('o', 7, 2, 3)), vec![5, 3]));
+    assert_eq!(run("avaiii s"), (Err(index_error('s', 7, 2, 3)), vec![5, 3]));
+}
+
+#[test]
+fn step_reports_the_same_errors_as_run() {
+    let mut interpreter = Interpreter::with_io("aaiiiii s", Config::default(), BufferIo::new("")).unwrap();
+    assert_eq!(step_to_end(&mut interpreter), Err(index_error('s', 8, 2, 5)));
+}
@@ -0,0 +1,38 @@
+// The stack capacity: 100 items by default, a custom limit or no limit at all.
+
+mod common;
+
+use ssl::Config;
+use ssl::InterpreterError;
+use crate::common::at;
+use crate::common::run_with;
+
+fn with_stack_size(max_stack_size: Option<usize>) -> Config {
+    Config { max_stack_size, ..Config::default() }
+}
+
+#[test]
+fn the_stack_holds_100_items_by_default() {
+    let (result, stack, _) = run_with(&"a".repeat(100), "", Config::default());
+    assert_eq!((result, stack.len()), (Ok(()), 100));
+    let expected = InterpreterError::StackOverflow { at: at('a', 100, 100), max_size: 100 };
+    assert_eq!(run_with(&"a".repeat(101), "", Config::default()).0, Err(expected));
+}
+
+#[test]
+fn a_custom_limit_is_reported_in_the_overflow() {
+    let expected = InterpreterError::StackOverflow { at: at('a', 3, 3), max_size: 3 };
+    assert_eq!(run_with("aaaa", "", with_stack_size(Some(3))), (Err(expected), vec![0, 0, 0], vec![]));
+    // Instructions pushing a result overflow too
+    let expected = InterpreterError::StackOverflow { at: at('q', 2, 2), max_size: 2 };
+    assert_eq!(run_with("aaq", "", with_stack_size(Some(2))).0, Err(expected));
+}
+
+#[test]
+fn an_unbounded_stack_grows_past_100_items() {
+    let (result, stack, _) = run_with(&"a".repeat(5000), "", with_stack_size(None));
+    assert_eq!((result, stack.len()), (Ok(()), 5000));
+    // `r` pushes the depth
+    let program = format!("{}r", "a".repeat(150));
+    assert_eq!(run_with(&program, "", with_stack_size(None)).1.last(), Some(&150));
+}